@@ -1,3 +1,5 @@
+pub mod maxsat;
+
 pub mod parser;
 
 pub mod sat;
@@ -1,37 +1,562 @@
-use std::env;
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    process::{self, Child, ChildStdout, Command, Stdio},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 
 use lutrix::{
-    parser::dimacs,
-    sat::{types::Variable, Solver},
+    maxsat,
+    parser::{
+        dimacs::{self, Mode},
+        opb::{self, Opb},
+        wcnf,
+    },
+    sat::{
+        types::{Model, Variable},
+        ApproxConfig, ApproxError, Comparison, InterruptHandle, Limits, ModelCounter, ProofFormat,
+        SolveResult, Solver, Statistics,
+    },
 };
 
+const USAGE: &str = "usage: lutrix [options] <input-file | ->
+       lutrix [options] opb <input-file | ->
+       lutrix [options] wcnf <input-file | ->
+       lutrix [options] count <input-file | ->
+
+modes:
+    opb                     read a pseudo-Boolean problem in the OPB format
+                            and minimize its objective if it has one
+    wcnf                    read a weighted MaxSAT problem in the WCNF format
+                            and minimize the weight of the falsified clauses
+    count                   count the models of a DIMACS CNF file, projected
+                            onto the variables of `c p show` lines
+
+options:
+    --verbose               print comments on the progress
+    --stats                 print a progress table and statistics
+    --proof <file>          write a DRAT proof of unsatisfiability, only for
+                            DIMACS CNF input
+    --binary-proof          use the binary DRAT format
+    --lenient               accept inputs not matching the header
+    --timeout <seconds>     give up after this many seconds
+    --max-conflicts <n>     give up after this many conflicts
+    --approx                estimate the count with random XOR constraints
+    --seed <n>              seed the XOR constraints of --approx";
+
+// exit codes of the SAT competition
+const EXIT_SAT: i32 = 10;
+const EXIT_UNSAT: i32 = 20;
+const EXIT_OPTIMUM: i32 = 30;
+
+// conflicts between two rows of the progress table
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+static INTERRUPT: OnceLock<InterruptHandle> = OnceLock::new();
+
+// the first SIGINT stops the search, a second one kills the process. Only
+// unix has `signal` with SIGINT = 2 and SIG_DFL = 0 in its C library; other
+// platforms keep the default handler.
+#[cfg(unix)]
+fn handle_sigint(handle: InterruptHandle) {
+    const SIGINT: i32 = 2;
+    const SIG_DFL: usize = 0;
+
+    extern "C" {
+        fn signal(signum: i32, handler: usize) -> usize;
+    }
+
+    extern "C" fn on_sigint(_: i32) {
+        if let Some(handle) = INTERRUPT.get() {
+            handle.interrupt();
+        }
+        // SAFETY: `signal` is async-signal-safe and SIG_DFL is a valid
+        // handler. The lines above only read the initialized `OnceLock` and
+        // store an atomic flag, which neither allocate nor take a lock.
+        unsafe { signal(SIGINT, SIG_DFL) };
+    }
+
+    if INTERRUPT.set(handle).is_ok() {
+        // SAFETY: `on_sigint` has the signature of a C signal handler, and
+        // `INTERRUPT` is set before it can run.
+        unsafe { signal(SIGINT, on_sigint as extern "C" fn(i32) as usize) };
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_sigint(_: InterruptHandle) {}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Dimacs,
+    Opb,
+    Wcnf,
+    Count,
+}
+
+struct Options {
+    input: String,
+    format: Format,
+    proof: Option<String>,
+    proof_format: ProofFormat,
+    mode: Mode,
+    verbose: bool,
+    stats: bool,
+    timeout: Option<Duration>,
+    max_conflicts: Option<u64>,
+    approx: Option<ApproxConfig>,
+}
+
+fn parse_args() -> Option<Options> {
+    let mut args = env::args().skip(1);
+    let mut input = None;
+    let mut format = Format::Dimacs;
+    let mut proof = None;
+    let mut proof_format = ProofFormat::Text;
+    let mut mode = Mode::Strict;
+    let mut verbose = false;
+    let mut stats = false;
+    let mut timeout = None;
+    let mut max_conflicts = None;
+    let mut approx = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--proof" => proof = Some(args.next()?),
+            "--binary-proof" => proof_format = ProofFormat::Binary,
+            "--lenient" => mode = Mode::Lenient,
+            "--verbose" => verbose = true,
+            "--stats" => stats = true,
+            "--timeout" => {
+                let seconds = args.next()?.parse::<f64>().ok()?;
+                timeout = Some(Duration::try_from_secs_f64(seconds).ok()?);
+            }
+            "--max-conflicts" => max_conflicts = Some(args.next()?.parse().ok()?),
+            "--approx" => approx = Some(approx.unwrap_or_default()),
+            "--seed" => {
+                let seed = args.next()?.parse().ok()?;
+                approx = Some(ApproxConfig {
+                    seed,
+                    ..approx.unwrap_or_default()
+                });
+            }
+            "opb" if input.is_none() && format == Format::Dimacs => format = Format::Opb,
+            "wcnf" if input.is_none() && format == Format::Dimacs => format = Format::Wcnf,
+            "count" if input.is_none() && format == Format::Dimacs => format = Format::Count,
+            _ if input.is_none() => input = Some(arg),
+            _ => return None,
+        }
+    }
+
+    // optimality and counts are not something a DRAT proof can show, and the
+    // clauses encoding PB constraints are not in the input a checker reads
+    if matches!(format, Format::Opb | Format::Wcnf | Format::Count) && proof.is_some() {
+        return None;
+    }
+
+    Some(Options {
+        input: input?,
+        format,
+        proof,
+        proof_format,
+        mode,
+        verbose,
+        stats,
+        timeout,
+        max_conflicts,
+        approx,
+    })
+}
+
+// `-` is stdin, compressed files are piped through the matching decompressor
+fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path == "-" {
+        return Ok(Box::new(BufReader::new(io::stdin())));
+    }
+
+    let decompressor = [
+        (".gz", "gzip"),
+        (".bz2", "bzip2"),
+        (".xz", "xz"),
+        (".lzma", "lzma"),
+        (".zst", "zstd"),
+    ]
+    .iter()
+    .find(|(ext, _)| path.ends_with(ext));
+
+    match decompressor {
+        Some((_, program)) => {
+            let mut child = Command::new(program)
+                .args(["-d", "-c", path])
+                .stdout(Stdio::piped())
+                .spawn()?;
+            let stdout = child.stdout.take().unwrap();
+            Ok(Box::new(BufReader::new(Decompressed {
+                program,
+                child,
+                stdout,
+            })))
+        }
+        None => Ok(Box::new(BufReader::new(File::open(path)?))),
+    }
+}
+
+// the output of a decompressor, which fails at the end unless the
+// decompressor succeeded, so that a corrupt or truncated archive is not read
+// as a shorter formula
+struct Decompressed {
+    program: &'static str,
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Read for Decompressed {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.stdout.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            let status = self.child.wait()?;
+            if !status.success() {
+                let message = format!("{} failed: {}", self.program, status);
+                return Err(io::Error::other(message));
+            }
+        }
+        Ok(n)
+    }
+}
+
 fn main() {
-    let args = env::args().collect::<Vec<String>>();
-    if args.len() < 2 {
-        println!("usage: lutrix <input-file>");
+    let start = Instant::now();
+    let options = match parse_args() {
+        Some(options) => options,
+        None => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+
+    if options.verbose {
+        println!("c reading file: {}", options.input);
+    }
+    let input = match open_input(&options.input) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{}: {}", options.input, err);
+            process::exit(1);
+        }
+    };
+    match options.format {
+        Format::Wcnf => return solve_wcnf(input, &options, start),
+        Format::Count => return count(input, &options),
+        _ => {}
+    }
+
+    let mut solver = Solver::new();
+    if let Some(path) = &options.proof {
+        let file = File::create(path).expect("cannot create proof file");
+        solver.set_proof(file, options.proof_format);
+    }
+    let problem = match options.format == Format::Opb {
+        true => match opb::read(input) {
+            Ok(problem) => {
+                problem.add_to(&mut solver);
+                Some(problem)
+            }
+            Err(err) => {
+                eprintln!("{}: {}", options.input, err);
+                process::exit(1);
+            }
+        },
+        false => {
+            if let Err(err) = dimacs::read(input, &mut solver, options.mode) {
+                eprintln!("{}: {}", options.input, err);
+                process::exit(1);
+            }
+            None
+        }
+    };
+
+    if options.stats {
+        print_progress_header();
+        solver.set_progress(PROGRESS_INTERVAL, print_progress);
+    }
+    handle_sigint(solver.interrupt_handle());
+    solver.set_limits(limits(&options, start));
+    if let Some(problem) = &problem {
+        solve_opb(&mut solver, problem, &options);
         return;
     }
 
-    println!("[*] reading file: {}", args[1]);
-    let input = std::fs::read_to_string(&args[1]).expect("cannot read file");
-    let formula = dimacs::parse(&input);
-    println!("[*] formula = {}", formula);
+    let result = solver.check();
+    finish(&mut solver, &options);
 
-    let mut solver = Solver::new();
-    solver.set_formula(formula);
-    match solver.check() {
-        true => {
-            println!("SAT");
+    match result {
+        SolveResult::Sat => {
+            println!("s SATISFIABLE");
 
             let model = solver.model();
-            let mut vars = model.keys().collect::<Vec<&Variable>>();
-            vars.sort();
-            for var in vars {
-                println!("x{} = {}", var, model[var]);
+            let mut vars = model.keys().cloned().collect::<Vec<Variable>>();
+            vars.sort_unstable();
+            if options.verbose {
+                for var in &vars {
+                    println!("c x{} = {}", var, model[var]);
+                }
             }
+            let values = vars.iter().map(|var| match model[var] {
+                true => *var as isize,
+                false => -(*var as isize),
+            });
+            print_values(values.chain(std::iter::once(0)));
+            process::exit(EXIT_SAT);
         }
-        false => {
-            println!("UNSAT");
+        SolveResult::Unsat => {
+            println!("s UNSATISFIABLE");
+            process::exit(EXIT_UNSAT);
+        }
+        SolveResult::Unknown(reason) => {
+            if options.verbose {
+                println!("c stopped: {:?}", reason);
+            }
+            println!("s UNKNOWN");
         }
     }
 }
+
+fn limits(options: &Options, start: Instant) -> Limits {
+    Limits {
+        conflicts: options.max_conflicts,
+        propagations: None,
+        deadline: options.timeout.map(|timeout| start + timeout),
+    }
+}
+
+fn finish(solver: &mut Solver, options: &Options) {
+    solver.flush_proof().expect("cannot write proof");
+    if options.stats {
+        print_progress(solver.stats());
+        print_stats(solver.stats());
+    }
+}
+
+// answers the decision problem, or improves the objective by one model at a
+// time until no cheaper one is left
+fn solve_opb(solver: &mut Solver, problem: &Opb, options: &Options) {
+    let mut best: Option<Model> = None;
+    let result = loop {
+        let result = solver.check();
+        if result != SolveResult::Sat {
+            break result;
+        }
+        let model = solver.model();
+        let Some(objective) = &problem.objective else {
+            best = Some(model);
+            break result;
+        };
+
+        let cost = problem.cost(&model).unwrap();
+        println!("o {}", cost);
+        best = Some(model);
+        solver.add_pb(objective, Comparison::AtMost, cost - 1);
+    };
+    finish(solver, options);
+
+    if let SolveResult::Unknown(reason) = result {
+        if options.verbose {
+            println!("c stopped: {:?}", reason);
+        }
+    }
+    let Some(model) = best else {
+        match result {
+            SolveResult::Unsat => {
+                println!("s UNSATISFIABLE");
+                process::exit(EXIT_UNSAT);
+            }
+            _ => {
+                println!("s UNKNOWN");
+                return;
+            }
+        }
+    };
+
+    let code = match result {
+        SolveResult::Unsat => {
+            println!("s OPTIMUM FOUND");
+            EXIT_OPTIMUM
+        }
+        _ => {
+            println!("s SATISFIABLE");
+            EXIT_SAT
+        }
+    };
+    print_values((1..=problem.num_vars).map(|var| match model.get(&var) {
+        Some(true) => format!("x{}", var),
+        _ => format!("-x{}", var),
+    }));
+    process::exit(code);
+}
+
+// prints an `o` line for every better model, and the best one as a string
+// of 0s and 1s as the MaxSAT Evaluations since 2022 expect
+fn solve_wcnf(input: Box<dyn BufRead>, options: &Options, start: Instant) {
+    let problem = match wcnf::read(input) {
+        Ok(problem) => problem,
+        Err(err) => {
+            eprintln!("{}: {}", options.input, err);
+            process::exit(1);
+        }
+    };
+    let mut solver = maxsat::Solver::new();
+    problem.add_to(&mut solver);
+    solver.set_progress(|cost| println!("o {}", cost));
+    handle_sigint(solver.interrupt_handle());
+    solver.set_limits(limits(options, start));
+
+    let result = solver.check();
+    if options.stats {
+        print_stats(solver.stats());
+    }
+    if let SolveResult::Unknown(reason) = result {
+        if options.verbose {
+            println!("c stopped: {:?}", reason);
+        }
+    }
+
+    if result == SolveResult::Unsat {
+        println!("s UNSATISFIABLE");
+        process::exit(EXIT_UNSAT);
+    }
+    let Some(model) = solver.model() else {
+        println!("s UNKNOWN");
+        return;
+    };
+
+    let code = match result {
+        SolveResult::Sat => {
+            println!("s OPTIMUM FOUND");
+            EXIT_OPTIMUM
+        }
+        _ => {
+            println!("s SATISFIABLE");
+            EXIT_SAT
+        }
+    };
+    let values = (1..=problem.num_vars)
+        .map(|var| match model.get(&var) {
+            Some(true) => '1',
+            _ => '0',
+        })
+        .collect::<String>();
+    println!("v {}", values);
+    process::exit(code);
+}
+
+// prints the count in the format of the model counting competitions
+fn count(input: Box<dyn BufRead>, options: &Options) {
+    let mut counter = ModelCounter::new();
+    if let Err(err) = dimacs::read(input, &mut counter, options.mode) {
+        eprintln!("{}: {}", options.input, err);
+        process::exit(1);
+    }
+
+    handle_sigint(counter.interrupt_handle());
+    let (count, exact) = match &options.approx {
+        Some(config) => match counter.approx_count(config) {
+            Ok(count) => (Some(count), false),
+            // the XORs never split the models finely enough
+            Err(ApproxError::NoEstimate) => (counter.count(), true),
+            Err(ApproxError::Interrupted) => (None, false),
+        },
+        None => (counter.count(), true),
+    };
+    match counter.is_projected() {
+        true => println!("c s type pmc"),
+        false => println!("c s type mc"),
+    }
+    let Some(count) = count else {
+        println!("s UNKNOWN");
+        return;
+    };
+    if count.is_zero() {
+        println!("s UNSATISFIABLE");
+        process::exit(EXIT_UNSAT);
+    }
+    println!("s SATISFIABLE");
+    println!("c s log10-estimate {:.6}", count.log10());
+    match exact {
+        true => println!("c s exact arb int {}", count),
+        false => println!("c s approx arb int {}", count),
+    }
+    process::exit(EXIT_SAT);
+}
+
+// prints `v` lines of the values
+fn print_values<T: std::fmt::Display, I: Iterator<Item = T>>(values: I) {
+    let mut line = String::from("v");
+    for value in values {
+        let token = format!(" {}", value);
+        if line.len() + token.len() > 78 {
+            println!("{}", line);
+            line = String::from("v");
+        }
+        line.push_str(&token);
+    }
+    println!("{}", line);
+}
+
+fn print_progress_header() {
+    println!(
+        "c {:>9} {:>11} {:>11} {:>13} {:>9} {:>11} {:>11}",
+        "seconds", "conflicts", "decisions", "propagations", "restarts", "learned", "deleted"
+    );
+}
+
+fn print_progress(stats: &Statistics) {
+    println!(
+        "c {:>9.2} {:>11} {:>11} {:>13} {:>9} {:>11} {:>11}",
+        stats.elapsed.as_secs_f64(),
+        stats.conflicts,
+        stats.decisions,
+        stats.propagations,
+        stats.restarts,
+        stats.learned,
+        stats.deleted
+    );
+}
+
+fn print_stats(stats: &Statistics) {
+    let seconds = stats.elapsed.as_secs_f64();
+    let per_second = |count: u64| match seconds > 0.0 {
+        true => count as f64 / seconds,
+        false => 0.0,
+    };
+    println!("c");
+    println!(
+        "c decisions    : {:>12} ({:.0}/s)",
+        stats.decisions,
+        per_second(stats.decisions)
+    );
+    println!(
+        "c propagations : {:>12} ({:.0}/s)",
+        stats.propagations,
+        per_second(stats.propagations)
+    );
+    println!(
+        "c conflicts    : {:>12} ({:.0}/s)",
+        stats.conflicts,
+        per_second(stats.conflicts)
+    );
+    println!("c restarts     : {:>12}", stats.restarts);
+    println!("c learned      : {:>12}", stats.learned);
+    println!("c deleted      : {:>12}", stats.deleted);
+    println!("c reduced      : {:>12}", stats.reduced);
+    println!("c eliminated   : {:>12}", stats.eliminated);
+    println!("c subsumed     : {:>12}", stats.subsumed);
+    println!("c strengthened : {:>12}", stats.strengthened);
+    println!("c substituted  : {:>12}", stats.substituted);
+    println!("c failed       : {:>12}", stats.failed_literals);
+    println!("c hyper-binary : {:>12}", stats.hyper_binary);
+    println!("c xor implied  : {:>12}", stats.xor_propagations);
+    println!("c pb implied   : {:>12}", stats.pb_propagations);
+    println!("c time         : {:>12.2} s", seconds);
+}
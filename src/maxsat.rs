@@ -0,0 +1,6 @@
+mod core_guided;
+
+mod linear;
+
+pub mod solver;
+pub use solver::*;
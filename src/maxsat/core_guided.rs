@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::sat::{types::Literal, SolveResult};
+
+use super::solver::Solver;
+
+impl Solver {
+    // ref: A. Morgado et al., Core-Guided MaxSAT with Soft Cardinality
+    // Constraints (2014), and A. Ignatiev et al., RC2: an Efficient MaxSAT
+    // Solver (2019). Each core raises the lower bound by its least weight,
+    // which is taken off its assumptions and put on a totalizer allowing one
+    // more of them to be falsified.
+    pub(crate) fn core_guided(&mut self) -> SolveResult {
+        // the weight left on every assumption
+        let mut weights: HashMap<Literal, u64> = HashMap::new();
+        for soft in &self.softs {
+            *weights.entry(soft.selector).or_default() += soft.weight;
+        }
+        // sums[i][j] holds if more than j of the literals of the i-th relaxed
+        // core are falsified, and is assumed false as `bounds` records
+        let mut sums: Vec<Vec<Literal>> = Vec::new();
+        let mut bounds: HashMap<Literal, (usize, usize)> = HashMap::new();
+
+        let mut lower = self.fixed_cost;
+        let mut stratum = weights.values().max().cloned().unwrap_or(0);
+        loop {
+            let mut assumptions = weights
+                .iter()
+                .filter(|&(_, &weight)| weight >= stratum)
+                .map(|(&literal, _)| literal)
+                .collect::<Vec<_>>();
+            assumptions.sort_unstable_by_key(|literal| literal.index());
+
+            match self.sat_solver.check_with_assumptions(&assumptions) {
+                SolveResult::Sat => {
+                    if self.improve() == lower {
+                        return SolveResult::Sat;
+                    }
+                    // the strata are done once every assumption holds
+                    match weights.values().filter(|&&weight| weight < stratum).max() {
+                        Some(&weight) => stratum = weight,
+                        None => return SolveResult::Sat,
+                    }
+                }
+                SolveResult::Unsat => {
+                    let core = self.sat_solver.failed_assumptions();
+                    if core.is_empty() {
+                        return SolveResult::Unsat;
+                    }
+                    let weight = core.iter().map(|l| weights[l]).min().unwrap();
+                    lower += weight;
+
+                    let mut relaxed = Vec::new();
+                    for literal in &core {
+                        let left = weights.get_mut(literal).unwrap();
+                        *left -= weight;
+                        if *left == 0 {
+                            weights.remove(literal);
+                        }
+                        if let Some(&(sum, j)) = bounds.get(literal) {
+                            if let Some(&output) = sums[sum].get(j + 1) {
+                                relaxed.push((-output, (sum, j + 1)));
+                            }
+                        }
+                    }
+                    if core.len() > 1 {
+                        let falsified = core.iter().map(|&l| -l).collect::<Vec<_>>();
+                        let outputs = self.sat_solver.totalizer(&falsified, falsified.len());
+                        for output in &outputs {
+                            self.sat_solver.freeze(output.var);
+                        }
+                        // one of them is falsified anyway
+                        relaxed.push((-outputs[1], (sums.len(), 1)));
+                        sums.push(outputs);
+                    }
+
+                    for (literal, bound) in relaxed {
+                        *weights.entry(literal).or_default() += weight;
+                        bounds.insert(literal, bound);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
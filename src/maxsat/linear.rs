@@ -0,0 +1,40 @@
+use crate::sat::SolveResult;
+
+use super::solver::Solver;
+
+impl Solver {
+    pub(crate) fn linear(&mut self) -> SolveResult {
+        // weights may take all of u64, so the bound is computed in i128
+        let total = self
+            .softs
+            .iter()
+            .map(|soft| soft.weight as i128)
+            .sum::<i128>();
+        let mut assumptions = Vec::new();
+        loop {
+            match self.sat_solver.check_with_assumptions(&assumptions) {
+                SolveResult::Sat => {}
+                SolveResult::Unsat if self.best.is_some() => return SolveResult::Sat,
+                result => return result,
+            }
+            let cost = self.improve() - self.fixed_cost;
+            if cost == 0 {
+                return SolveResult::Sat;
+            }
+
+            // the weight of the falsified selectors must go below `cost`. The
+            // bound only holds under `active`, so that later calls start
+            // from the hard clauses again.
+            let active = self.sat_solver.new_literal();
+            let mut terms = self
+                .softs
+                .iter()
+                .map(|soft| (-(soft.weight as i128), -soft.selector))
+                .collect::<Vec<_>>();
+            terms.push((-total, active));
+            self.sat_solver
+                .add_pb_at_least(terms.into_iter(), -(cost as i128 - 1 + total));
+            assumptions = vec![active];
+        }
+    }
+}
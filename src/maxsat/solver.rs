@@ -0,0 +1,163 @@
+use crate::sat::{self, types::*, InterruptHandle, Limits, SolveResult, Statistics};
+
+/// How the optimum is searched for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// OLL as in RC2: unsatisfiable cores raise the lower bound and are
+    /// relaxed with totalizers, taking the soft clauses in strata of
+    /// decreasing weight.
+    #[default]
+    CoreGuided,
+    /// SAT-UNSAT search: every model is followed by a constraint asking for a
+    /// cheaper one.
+    Linear,
+}
+
+/// A weighted partial MaxSAT solver: the hard clauses must hold, and the
+/// total weight of the falsified soft clauses is minimized.
+pub struct Solver {
+    pub(crate) sat_solver: sat::Solver,
+    pub(crate) algorithm: Algorithm,
+    pub(crate) softs: Vec<Soft>,
+    // the weight of the empty soft clauses
+    pub(crate) fixed_cost: u64,
+    // the weight of all soft clauses, which bounds every cost
+    pub(crate) total_weight: u64,
+    pub(crate) best: Option<(u64, Model)>,
+    pub(crate) progress: Option<Box<dyn FnMut(u64)>>,
+}
+
+pub(crate) struct Soft {
+    pub(crate) literals: Vec<Literal>,
+    pub(crate) weight: u64,
+    // assumed to make the clause hold
+    pub(crate) selector: Literal,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::with_algorithm(Algorithm::default())
+    }
+
+    pub fn with_algorithm(algorithm: Algorithm) -> Self {
+        Self {
+            sat_solver: sat::Solver::new(),
+            algorithm,
+            softs: Vec::new(),
+            fixed_cost: 0,
+            total_weight: 0,
+            best: None,
+            progress: None,
+        }
+    }
+
+    pub fn new_literal(&mut self) -> Literal {
+        self.sat_solver.new_literal()
+    }
+
+    pub fn reserve_variables(&mut self, num_vars: usize) {
+        self.sat_solver.reserve_variables(num_vars);
+    }
+
+    pub fn add_clause(&mut self, literals: &[Literal]) {
+        self.sat_solver.add_clause(literals);
+    }
+
+    /// Adds a clause costing `weight` when falsified. The weights of all soft
+    /// clauses must add up to at most `u64::MAX`.
+    pub fn add_soft_clause(&mut self, literals: &[Literal], weight: u64) {
+        if weight == 0 {
+            return;
+        }
+        self.total_weight = self
+            .total_weight
+            .checked_add(weight)
+            .expect("the total weight of the soft clauses exceeds u64::MAX");
+        let selector = match literals {
+            [] => {
+                self.fixed_cost += weight;
+                return;
+            }
+            [literal] => *literal,
+            _ => {
+                let selector = self.sat_solver.new_literal();
+                let mut clause = literals.to_vec();
+                clause.push(-selector);
+                self.sat_solver.add_clause(&clause);
+                selector
+            }
+        };
+        // selectors are assumed in later calls, so they are never eliminated
+        self.sat_solver.freeze(selector.var);
+        self.softs.push(Soft {
+            literals: literals.to_vec(),
+            weight,
+            selector,
+        });
+    }
+
+    /// Sets the limits of every call to the underlying SAT solver.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.sat_solver.set_limits(limits);
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.sat_solver.interrupt_handle()
+    }
+
+    /// Calls `callback` with the cost of every better model found.
+    pub fn set_progress<F: FnMut(u64) + 'static>(&mut self, callback: F) {
+        self.progress = Some(Box::new(callback));
+    }
+
+    /// Searches for a model of the hard clauses with the least cost. `Sat`
+    /// means the model is optimal. After `Unknown`, `model` holds the best
+    /// one found so far, if any.
+    pub fn check(&mut self) -> SolveResult {
+        self.best = None;
+        match self.algorithm {
+            Algorithm::CoreGuided => self.core_guided(),
+            Algorithm::Linear => self.linear(),
+        }
+    }
+
+    pub fn stats(&self) -> &Statistics {
+        self.sat_solver.stats()
+    }
+
+    pub fn model(&self) -> Option<Model> {
+        self.best.as_ref().map(|(_, model)| model.clone())
+    }
+
+    /// The total weight of the soft clauses falsified by `model`.
+    pub fn cost(&self) -> Option<u64> {
+        self.best.as_ref().map(|&(cost, _)| cost)
+    }
+
+    // records the model of the last call if it is the best one so far
+    pub(crate) fn improve(&mut self) -> u64 {
+        let model = self.sat_solver.model();
+        let holds = |l: &Literal| model[&l.var] != l.inverted;
+        let cost = self.fixed_cost
+            + self
+                .softs
+                .iter()
+                .filter(|soft| !soft.literals.iter().any(holds))
+                .map(|soft| soft.weight)
+                .sum::<u64>();
+
+        if self.best.as_ref().is_none_or(|&(best, _)| cost < best) {
+            self.best = Some((cost, model));
+            if let Some(progress) = self.progress.as_mut() {
+                progress(cost);
+            }
+        }
+        cost
+    }
+}
@@ -1,3 +1,14 @@
 pub mod dimacs;
 
+pub mod error;
+pub use error::*;
+
+pub mod gcnf;
+
+mod lines;
+
+pub mod opb;
+
 pub mod smtlib;
+
+pub mod wcnf;
@@ -1,42 +1,296 @@
-use crate::sat::types::*;
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
 
-pub fn parse(input: &str) -> Cnf {
-    let mut tokens = input.split_ascii_whitespace().peekable();
-    while tokens.peek().unwrap() != &"p" {
-        tokens.next().unwrap();
+use crate::sat::{count::ModelCounter, solver::Solver, types::*};
+
+use super::{
+    error::ParseError,
+    lines::{read_lines, tokenize},
+};
+
+/// Receives the clauses of a DIMACS file while it is read.
+pub trait ClauseSink {
+    /// Called with the values of the `p cnf` header.
+    fn header(&mut self, _num_vars: usize, _num_clauses: usize) {}
+
+    fn add_clause(&mut self, literals: &[Literal]);
+
+    /// Called with the variables of a `c p show` line, which projects model
+    /// counting onto them.
+    fn show(&mut self, _vars: &[Variable]) {}
+}
+
+impl ClauseSink for Cnf {
+    fn add_clause(&mut self, literals: &[Literal]) {
+        Cnf::add_clause(self, literals);
+    }
+}
+
+impl ClauseSink for Solver {
+    fn header(&mut self, num_vars: usize, _num_clauses: usize) {
+        self.reserve_variables(num_vars);
     }
 
-    if tokens.next().unwrap() != "p" || tokens.next().unwrap() != "cnf" {
-        panic!("requires DIMACS format");
+    fn add_clause(&mut self, literals: &[Literal]) {
+        Solver::add_clause(self, literals);
     }
+}
+
+impl ClauseSink for ModelCounter {
+    fn header(&mut self, num_vars: usize, _num_clauses: usize) {
+        self.reserve_variables(num_vars);
+    }
+
+    fn add_clause(&mut self, literals: &[Literal]) {
+        ModelCounter::add_clause(self, literals);
+    }
+
+    fn show(&mut self, vars: &[Variable]) {
+        self.add_projection(vars);
+    }
+}
 
-    let _num_of_variables = tokens.next().unwrap().parse::<usize>().unwrap();
-    let num_of_clauses = tokens.next().unwrap().parse::<usize>().unwrap();
+/// The most variables a file may declare or use, as the solver allocates
+/// memory for every variable up front.
+pub const MAX_VARIABLES: usize = 1 << 26;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The clauses and variables must match the `p cnf` header exactly.
+    Strict,
+    /// Mismatches with the header are accepted, as well as a missing `0` after
+    /// the last clause and a SATLIB-style `%` end marker.
+    Lenient,
+}
+
+pub type DimacsError = ParseError<DimacsErrorKind>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DimacsErrorKind {
+    MissingHeader,
+    InvalidHeader,
+    DuplicateHeader,
+    InvalidLiteral(String),
+    VariableOutOfRange { var: Variable, max: usize },
+    TooManyVariables { max: usize },
+    TooManyClauses { expected: usize },
+    TooFewClauses { expected: usize, found: usize },
+    UnterminatedClause,
+    Io(String),
+}
+
+impl fmt::Display for DimacsErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::DimacsErrorKind::*;
+        match self {
+            MissingHeader => write!(f, "missing `p cnf` header"),
+            InvalidHeader => write!(f, "expected `p cnf <variables> <clauses>`"),
+            DuplicateHeader => write!(f, "duplicate `p cnf` header"),
+            InvalidLiteral(token) => write!(f, "invalid literal `{}`", token),
+            VariableOutOfRange { var, max } => {
+                write!(f, "variable {} exceeds the declared maximum {}", var, max)
+            }
+            TooManyVariables { max } => write!(f, "more than {} variables", max),
+            TooManyClauses { expected } => write!(f, "more than {} clauses", expected),
+            TooFewClauses { expected, found } => {
+                write!(f, "expected {} clauses, found {}", expected, found)
+            }
+            UnterminatedClause => write!(f, "clause is not terminated by 0"),
+            Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for DimacsErrorKind {
+    fn from(err: io::Error) -> Self {
+        DimacsErrorKind::Io(err.to_string())
+    }
+}
+
+pub fn parse(input: &str) -> Result<Cnf, DimacsError> {
+    parse_with(input, Mode::Strict)
+}
 
+pub fn parse_with(input: &str, mode: Mode) -> Result<Cnf, DimacsError> {
     let mut cnf = Cnf::new();
-    for _ in 0..num_of_clauses {
-        let mut literals = Vec::new();
+    read(input.as_bytes(), &mut cnf, mode)?;
+    Ok(cnf)
+}
+
+/// Reads a DIMACS file line by line and passes the clauses on to `sink`
+/// without keeping them in memory.
+pub fn read<R: BufRead, S: ClauseSink>(
+    reader: R,
+    sink: &mut S,
+    mode: Mode,
+) -> Result<(), DimacsError> {
+    let mut parser = Parser::new(mode);
+    read_lines(reader, |line| parser.parse_line(line, sink))?;
+    parser.finish(sink)
+}
+
+struct Parser {
+    mode: Mode,
+    line: usize,
+    header: Option<(usize, usize)>,
+    num_clauses: usize,
+    literals: Vec<Literal>,
+}
+
+impl Parser {
+    fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            line: 0,
+            header: None,
+            num_clauses: 0,
+            literals: Vec::new(),
+        }
+    }
+
+    // returns false at the end marker
+    fn parse_line<S: ClauseSink>(&mut self, line: &str, sink: &mut S) -> Result<bool, DimacsError> {
+        self.line += 1;
+
+        let mut tokens = tokenize(line).peekable();
+        match tokens.peek() {
+            None => return Ok(true),
+            Some((_, token)) if token.starts_with('c') => {
+                let tokens = tokens.collect::<Vec<_>>();
+                if let [(_, "c"), (_, "p"), (_, "show"), vars @ ..] = tokens.as_slice() {
+                    self.parse_show(vars, sink)?;
+                }
+                return Ok(true);
+            }
+            Some((_, "%")) if self.mode == Mode::Lenient => return Ok(false),
+            Some(&(column, "p")) => {
+                if self.header.is_some() {
+                    return Err(self.error(column, DimacsErrorKind::DuplicateHeader));
+                }
+                let header = tokens.map(|(_, t)| t).collect::<Vec<_>>();
+                self.header = match header.as_slice() {
+                    ["p", "cnf", vars, clauses] => match (vars.parse(), clauses.parse()) {
+                        (Ok(vars), Ok(clauses)) => Some((vars, clauses)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match self.header {
+                    Some((vars, _)) if vars > MAX_VARIABLES => {
+                        let kind = DimacsErrorKind::TooManyVariables { max: MAX_VARIABLES };
+                        return Err(self.error(column, kind));
+                    }
+                    Some((vars, clauses)) => sink.header(vars, clauses),
+                    None => return Err(self.error(column, DimacsErrorKind::InvalidHeader)),
+                }
+                return Ok(true);
+            }
+            _ => {}
+        }
+
+        let (max_var, max_clauses) = match self.header {
+            Some(header) => header,
+            None => return Err(self.error(1, DimacsErrorKind::MissingHeader)),
+        };
+
+        for (column, token) in tokens {
+            let value = token
+                .parse::<isize>()
+                .map_err(|_| self.error(column, DimacsErrorKind::InvalidLiteral(token.into())))?;
+
+            if value == 0 {
+                if self.num_clauses == max_clauses && self.mode == Mode::Strict {
+                    let kind = DimacsErrorKind::TooManyClauses {
+                        expected: max_clauses,
+                    };
+                    return Err(self.error(column, kind));
+                }
+                sink.add_clause(&self.literals);
+                self.literals.clear();
+                self.num_clauses += 1;
+                continue;
+            }
+
+            let literal = parse_literal(value);
+            if literal.var > MAX_VARIABLES {
+                let kind = DimacsErrorKind::TooManyVariables { max: MAX_VARIABLES };
+                return Err(self.error(column, kind));
+            }
+            if literal.var > max_var && self.mode == Mode::Strict {
+                let kind = DimacsErrorKind::VariableOutOfRange {
+                    var: literal.var,
+                    max: max_var,
+                };
+                return Err(self.error(column, kind));
+            }
+            self.literals.push(literal);
+        }
+
+        Ok(true)
+    }
+
+    // the variables of `c p show`, terminated by 0
+    fn parse_show<S: ClauseSink>(
+        &self,
+        tokens: &[(usize, &str)],
+        sink: &mut S,
+    ) -> Result<(), DimacsError> {
+        let mut vars = Vec::new();
+        for &(column, token) in tokens {
+            match token.parse::<Variable>() {
+                Ok(0) => break,
+                Ok(var) if var > MAX_VARIABLES => {
+                    let kind = DimacsErrorKind::TooManyVariables { max: MAX_VARIABLES };
+                    return Err(self.error(column, kind));
+                }
+                Ok(var) => vars.push(var),
+                Err(_) => {
+                    let kind = DimacsErrorKind::InvalidLiteral(token.into());
+                    return Err(self.error(column, kind));
+                }
+            }
+        }
+        sink.show(&vars);
+        Ok(())
+    }
+
+    fn finish<S: ClauseSink>(&mut self, sink: &mut S) -> Result<(), DimacsError> {
+        let (_, max_clauses) = match self.header {
+            Some(header) => header,
+            None => return Err(self.error(1, DimacsErrorKind::MissingHeader)),
+        };
 
-        loop {
-            let token = tokens.next().unwrap();
-            let var = token.parse::<isize>().unwrap();
-            if var == 0 {
-                break;
+        if !self.literals.is_empty() {
+            if self.mode == Mode::Strict {
+                return Err(self.error(1, DimacsErrorKind::UnterminatedClause));
             }
+            sink.add_clause(&self.literals);
+            self.literals.clear();
+            self.num_clauses += 1;
+        }
 
-            literals.push(parse_literal(var));
+        if self.num_clauses < max_clauses && self.mode == Mode::Strict {
+            let kind = DimacsErrorKind::TooFewClauses {
+                expected: max_clauses,
+                found: self.num_clauses,
+            };
+            return Err(self.error(1, kind));
         }
 
-        cnf.add_clause(&literals);
+        Ok(())
     }
 
-    cnf
+    fn error(&self, column: usize, kind: DimacsErrorKind) -> DimacsError {
+        DimacsError {
+            line: self.line.max(1),
+            column,
+            kind,
+        }
+    }
 }
 
 fn parse_literal(var: isize) -> Literal {
-    if var > 0 {
-        Literal::new(var as usize, false)
-    } else {
-        Literal::new(-var as usize, true)
-    }
+    Literal::new(var.unsigned_abs(), var < 0)
 }
@@ -0,0 +1,18 @@
+use std::fmt;
+
+/// An error at a 1-based line and column of an input file, of a `kind`
+/// specific to its format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError<K> {
+    pub line: usize,
+    pub column: usize,
+    pub kind: K,
+}
+
+impl<K: fmt::Display> fmt::Display for ParseError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl<K: fmt::Debug + fmt::Display> std::error::Error for ParseError<K> {}
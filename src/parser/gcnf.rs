@@ -0,0 +1,182 @@
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
+
+use crate::sat::{solver::Solver, types::*};
+
+use super::{
+    error::ParseError,
+    lines::{read_lines, tokenize},
+};
+
+/// A formula in the group-oriented CNF of the MUS competitions: each clause
+/// starts with its group `{g}`, where group 0 holds the clauses outside of
+/// any group and the header `p gcnf <variables> <clauses> <groups>` gives the
+/// last group.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Gcnf {
+    /// The declared number of variables, raised to the highest one used.
+    pub num_vars: usize,
+    pub num_groups: usize,
+    pub clauses: Vec<(usize, Vec<Literal>)>,
+}
+
+impl Gcnf {
+    /// Adds the clauses to `solver` and returns the selectors of the groups
+    /// 1 to `num_groups`, to be passed to `minimal_unsat_subset`.
+    pub fn add_to(&self, solver: &mut Solver) -> Vec<Literal> {
+        solver.reserve_variables(self.num_vars);
+        let groups = (0..self.num_groups)
+            .map(|_| solver.new_group())
+            .collect::<Vec<_>>();
+        for (group, clause) in &self.clauses {
+            match group {
+                0 => solver.add_clause(clause),
+                _ => solver.add_group_clause(groups[group - 1], clause),
+            }
+        }
+        groups
+    }
+}
+
+pub type GcnfError = ParseError<GcnfErrorKind>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GcnfErrorKind {
+    MissingHeader,
+    InvalidHeader,
+    DuplicateHeader,
+    InvalidGroup(String),
+    GroupOutOfRange { group: usize, max: usize },
+    InvalidLiteral(String),
+    UnterminatedClause,
+    Io(String),
+}
+
+impl fmt::Display for GcnfErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::GcnfErrorKind::*;
+        match self {
+            MissingHeader => write!(f, "missing `p gcnf` header"),
+            InvalidHeader => write!(f, "expected `p gcnf <variables> <clauses> <groups>`"),
+            DuplicateHeader => write!(f, "duplicate `p gcnf` header"),
+            InvalidGroup(token) => write!(f, "expected a group `{{<n>}}`, found `{}`", token),
+            GroupOutOfRange { group, max } => {
+                write!(f, "group {} exceeds the declared maximum {}", group, max)
+            }
+            InvalidLiteral(token) => write!(f, "invalid literal `{}`", token),
+            UnterminatedClause => write!(f, "clause is not terminated by 0"),
+            Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for GcnfErrorKind {
+    fn from(err: io::Error) -> Self {
+        GcnfErrorKind::Io(err.to_string())
+    }
+}
+
+pub fn parse(input: &str) -> Result<Gcnf, GcnfError> {
+    read(input.as_bytes())
+}
+
+pub fn read<R: BufRead>(reader: R) -> Result<Gcnf, GcnfError> {
+    let mut parser = Parser::default();
+    read_lines(reader, |line| parser.parse_line(line).map(|()| true))?;
+    parser.finish()
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    header: bool,
+    gcnf: Gcnf,
+    // the clause being read and its group
+    clause: Option<(usize, Vec<Literal>)>,
+}
+
+impl Parser {
+    fn parse_line(&mut self, line: &str) -> Result<(), GcnfError> {
+        self.line += 1;
+
+        let mut tokens = tokenize(line).peekable();
+        match tokens.peek() {
+            None => return Ok(()),
+            Some((_, token)) if token.starts_with('c') => return Ok(()),
+            Some(&(column, "p")) => {
+                if self.header {
+                    return Err(self.error(column, GcnfErrorKind::DuplicateHeader));
+                }
+                let header = tokens.map(|(_, t)| t).collect::<Vec<_>>();
+                let header = match header.as_slice() {
+                    ["p", "gcnf", vars, clauses, groups] => {
+                        match (vars.parse(), clauses.parse::<usize>(), groups.parse()) {
+                            (Ok(vars), Ok(_), Ok(groups)) => Some((vars, groups)),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                let Some((vars, groups)) = header else {
+                    return Err(self.error(column, GcnfErrorKind::InvalidHeader));
+                };
+                self.gcnf.num_vars = self.gcnf.num_vars.max(vars);
+                self.gcnf.num_groups = groups;
+                self.header = true;
+                return Ok(());
+            }
+            _ => {}
+        }
+        if !self.header {
+            return Err(self.error(1, GcnfErrorKind::MissingHeader));
+        }
+
+        for (column, token) in tokens {
+            if self.clause.is_none() {
+                let group = token
+                    .strip_prefix('{')
+                    .and_then(|t| t.strip_suffix('}'))
+                    .and_then(|t| t.parse::<usize>().ok())
+                    .ok_or_else(|| self.error(column, GcnfErrorKind::InvalidGroup(token.into())))?;
+                if group > self.gcnf.num_groups {
+                    let kind = GcnfErrorKind::GroupOutOfRange {
+                        group,
+                        max: self.gcnf.num_groups,
+                    };
+                    return Err(self.error(column, kind));
+                }
+                self.clause = Some((group, Vec::new()));
+                continue;
+            }
+
+            let value = token
+                .parse::<isize>()
+                .map_err(|_| self.error(column, GcnfErrorKind::InvalidLiteral(token.into())))?;
+            if value != 0 {
+                let literal = Literal::new(value.unsigned_abs(), value < 0);
+                self.gcnf.num_vars = self.gcnf.num_vars.max(literal.var);
+                self.clause.as_mut().unwrap().1.push(literal);
+                continue;
+            }
+            self.gcnf.clauses.push(self.clause.take().unwrap());
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Gcnf, GcnfError> {
+        if self.clause.is_some() {
+            return Err(self.error(1, GcnfErrorKind::UnterminatedClause));
+        }
+        Ok(self.gcnf)
+    }
+
+    fn error(&self, column: usize, kind: GcnfErrorKind) -> GcnfError {
+        GcnfError {
+            line: self.line.max(1),
+            column,
+            kind,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::{self, BufRead};
+
+use super::error::ParseError;
+
+/// Passes the lines of `reader` to `parse` until it returns false. A failed
+/// read becomes an error of the kind converted from the `io::Error`.
+pub(crate) fn read_lines<R, K, F>(mut reader: R, mut parse: F) -> Result<(), ParseError<K>>
+where
+    R: BufRead,
+    K: From<io::Error>,
+    F: FnMut(&str) -> Result<bool, ParseError<K>>,
+{
+    let mut line = String::new();
+    let mut number = 0;
+    loop {
+        line.clear();
+        number += 1;
+        match reader.read_line(&mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(err) => {
+                return Err(ParseError {
+                    line: number,
+                    column: 1,
+                    kind: K::from(err),
+                })
+            }
+        }
+        if !parse(&line)? {
+            return Ok(());
+        }
+    }
+}
+
+/// Yields the whitespace-separated tokens of a line with their 1-based
+/// columns.
+pub(crate) fn tokenize(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_ascii_whitespace()
+        .map(move |token| (token.as_ptr() as usize - line.as_ptr() as usize + 1, token))
+}
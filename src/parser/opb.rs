@@ -0,0 +1,321 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use crate::sat::{pb::Comparison, solver::Solver, types::*};
+
+use super::{
+    error::ParseError,
+    lines::{self, read_lines},
+};
+
+/// A linear pseudo-Boolean problem in the OPB format of the PB competitions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Opb {
+    /// The declared number of variables, raised to the highest one used.
+    pub num_vars: usize,
+    /// Terms of the sum to minimize.
+    pub objective: Option<Vec<(i64, Literal)>>,
+    pub constraints: Vec<Constraint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub terms: Vec<(i64, Literal)>,
+    pub cmp: Comparison,
+    pub rhs: i64,
+}
+
+impl Opb {
+    /// Adds the constraints to `solver`; the objective is left to the caller.
+    pub fn add_to(&self, solver: &mut Solver) {
+        solver.reserve_variables(self.num_vars);
+        for constraint in &self.constraints {
+            solver.add_pb(&constraint.terms, constraint.cmp, constraint.rhs);
+        }
+    }
+
+    /// The value of the objective under `model`, if there is one.
+    pub fn cost(&self, model: &Model) -> Option<i64> {
+        let objective = self.objective.as_ref()?;
+        let holds = |l: &Literal| model.get(&l.var).cloned().unwrap_or(false) != l.inverted;
+        Some(
+            objective
+                .iter()
+                .filter(|(_, l)| holds(l))
+                .map(|(a, _)| a)
+                .sum(),
+        )
+    }
+}
+
+pub type OpbError = ParseError<OpbErrorKind>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpbErrorKind {
+    InvalidCoefficient(String),
+    InvalidLiteral(String),
+    NonLinearTerm,
+    UnexpectedToken(String),
+    MissingRelation,
+    MissingRightHandSide,
+    DuplicateObjective,
+    UnterminatedConstraint,
+    Io(String),
+}
+
+impl fmt::Display for OpbErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::OpbErrorKind::*;
+        match self {
+            InvalidCoefficient(token) => write!(f, "invalid coefficient `{}`", token),
+            InvalidLiteral(token) => write!(f, "invalid literal `{}`", token),
+            NonLinearTerm => write!(f, "non-linear terms are not supported"),
+            UnexpectedToken(token) => write!(f, "unexpected `{}`", token),
+            MissingRelation => write!(f, "constraint has no `>=`, `<=` or `=`"),
+            MissingRightHandSide => write!(f, "constraint has no right-hand side"),
+            DuplicateObjective => write!(f, "more than one objective"),
+            UnterminatedConstraint => write!(f, "constraint is not terminated by `;`"),
+            Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for OpbErrorKind {
+    fn from(err: io::Error) -> Self {
+        OpbErrorKind::Io(err.to_string())
+    }
+}
+
+pub fn parse(input: &str) -> Result<Opb, OpbError> {
+    read(input.as_bytes())
+}
+
+pub fn read<R: BufRead>(reader: R) -> Result<Opb, OpbError> {
+    let mut parser = Parser::default();
+    read_lines(reader, |line| parser.parse_line(line).map(|()| true))?;
+    parser.finish()
+}
+
+/// Writes `opb` with `<=` constraints turned into `>=` ones, as the format
+/// only has the latter.
+pub fn write<W: Write>(opb: &Opb, mut writer: W) -> io::Result<()> {
+    write!(writer, "{}", opb)
+}
+
+impl fmt::Display for Opb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "* #variable= {} #constraint= {}",
+            self.num_vars,
+            self.constraints.len()
+        )?;
+        if let Some(objective) = &self.objective {
+            write!(f, "min:")?;
+            write_terms(f, objective.iter().cloned())?;
+            writeln!(f, " ;")?;
+        }
+        for constraint in &self.constraints {
+            let (terms, rhs) = match constraint.cmp {
+                Comparison::AtMost => (
+                    constraint.terms.iter().map(|&(a, l)| (-a, l)).collect(),
+                    -constraint.rhs,
+                ),
+                _ => (constraint.terms.clone(), constraint.rhs),
+            };
+            write_terms(f, terms.into_iter())?;
+            let relation = match constraint.cmp {
+                Comparison::Equal => "=",
+                _ => ">=",
+            };
+            writeln!(f, " {} {} ;", relation, rhs)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_terms<I: Iterator<Item = (i64, Literal)>>(
+    f: &mut fmt::Formatter<'_>,
+    terms: I,
+) -> fmt::Result {
+    for (a, literal) in terms {
+        let negation = if literal.inverted { "~" } else { "" };
+        write!(f, " {:+} {}x{}", a, negation, literal.var)?;
+    }
+    Ok(())
+}
+
+// the statement being read, which may span several lines
+#[derive(Default)]
+struct Statement {
+    started: bool,
+    objective: bool,
+    terms: Vec<(i64, Literal)>,
+    // a coefficient waiting for its literal
+    coefficient: Option<i64>,
+    cmp: Option<Comparison>,
+    rhs: Option<i64>,
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    opb: Opb,
+    statement: Statement,
+}
+
+impl Parser {
+    fn parse_line(&mut self, line: &str) -> Result<(), OpbError> {
+        self.line += 1;
+        if line.starts_with('*') {
+            self.parse_comment(line);
+            return Ok(());
+        }
+
+        for (column, token) in tokenize(line) {
+            self.parse_token(column, token)?;
+        }
+        Ok(())
+    }
+
+    // `* #variable= 5 #constraint= 4` declares the number of variables
+    fn parse_comment(&mut self, line: &str) {
+        let mut tokens = line.split_ascii_whitespace();
+        while let Some(token) = tokens.next() {
+            if token == "#variable=" {
+                if let Some(Ok(num_vars)) = tokens.next().map(str::parse) {
+                    self.opb.num_vars = self.opb.num_vars.max(num_vars);
+                }
+            }
+        }
+    }
+
+    fn parse_token(&mut self, column: usize, token: &str) -> Result<(), OpbError> {
+        let statement = &mut self.statement;
+        match token {
+            "min:" if !statement.started => {
+                if self.opb.objective.is_some() {
+                    return Err(self.error(column, OpbErrorKind::DuplicateObjective));
+                }
+                statement.started = true;
+                statement.objective = true;
+            }
+            ";" => self.finish_statement(column)?,
+            ">=" | "<=" | "=" => {
+                if statement.objective || statement.cmp.is_some() {
+                    return Err(self.unexpected(column, token));
+                }
+                if statement.coefficient.is_some() {
+                    return Err(self.error(column, OpbErrorKind::InvalidLiteral(token.into())));
+                }
+                statement.started = true;
+                statement.cmp = Some(match token {
+                    ">=" => Comparison::AtLeast,
+                    "<=" => Comparison::AtMost,
+                    _ => Comparison::Equal,
+                });
+            }
+            _ if statement.cmp.is_some() => {
+                if statement.rhs.is_some() {
+                    return Err(self.unexpected(column, token));
+                }
+                let rhs = parse_integer(token).ok_or_else(|| {
+                    self.error(column, OpbErrorKind::InvalidCoefficient(token.into()))
+                })?;
+                self.statement.rhs = Some(rhs);
+            }
+            _ => match statement.coefficient.take() {
+                Some(a) => {
+                    let literal = parse_literal(token).ok_or_else(|| {
+                        self.error(column, OpbErrorKind::InvalidLiteral(token.into()))
+                    })?;
+                    self.opb.num_vars = self.opb.num_vars.max(literal.var);
+                    self.statement.terms.push((a, literal));
+                }
+                None => {
+                    if parse_literal(token).is_some() && !statement.terms.is_empty() {
+                        return Err(self.error(column, OpbErrorKind::NonLinearTerm));
+                    }
+                    let a = parse_integer(token).ok_or_else(|| {
+                        self.error(column, OpbErrorKind::InvalidCoefficient(token.into()))
+                    })?;
+                    self.statement.started = true;
+                    self.statement.coefficient = Some(a);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn finish_statement(&mut self, column: usize) -> Result<(), OpbError> {
+        let statement = std::mem::take(&mut self.statement);
+        if statement.coefficient.is_some() {
+            return Err(self.error(column, OpbErrorKind::InvalidLiteral(";".into())));
+        }
+        if statement.objective {
+            self.opb.objective = Some(statement.terms);
+            return Ok(());
+        }
+
+        let cmp = statement
+            .cmp
+            .ok_or_else(|| self.error(column, OpbErrorKind::MissingRelation))?;
+        let rhs = statement
+            .rhs
+            .ok_or_else(|| self.error(column, OpbErrorKind::MissingRightHandSide))?;
+        self.opb.constraints.push(Constraint {
+            terms: statement.terms,
+            cmp,
+            rhs,
+        });
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Opb, OpbError> {
+        if self.statement.started {
+            return Err(self.error(1, OpbErrorKind::UnterminatedConstraint));
+        }
+        Ok(self.opb)
+    }
+
+    fn unexpected(&self, column: usize, token: &str) -> OpbError {
+        self.error(column, OpbErrorKind::UnexpectedToken(token.into()))
+    }
+
+    fn error(&self, column: usize, kind: OpbErrorKind) -> OpbError {
+        OpbError {
+            line: self.line.max(1),
+            column,
+            kind,
+        }
+    }
+}
+
+// yields the tokens of a line with their 1-based columns, splitting off a
+// `;` that ends a token
+fn tokenize(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    lines::tokenize(line).flat_map(|(column, token)| match token.strip_suffix(';') {
+        Some(rest) if !rest.is_empty() => {
+            vec![(column, rest), (column + rest.len(), ";")]
+        }
+        _ => vec![(column, token)],
+    })
+}
+
+fn parse_integer(token: &str) -> Option<i64> {
+    token.strip_prefix('+').unwrap_or(token).parse().ok()
+}
+
+// `x3` or its negation `~x3`
+fn parse_literal(token: &str) -> Option<Literal> {
+    let (inverted, name) = match token.strip_prefix('~') {
+        Some(name) => (true, name),
+        None => (false, token),
+    };
+    match name.strip_prefix('x')?.parse() {
+        Ok(var) if var > 0 => Some(Literal::new(var, inverted)),
+        _ => None,
+    }
+}
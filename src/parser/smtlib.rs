@@ -1,6 +1,42 @@
+use std::fmt;
+
 use crate::smt::ast::*;
 
-pub fn parse(input: &str) -> Problem {
+use super::error::ParseError;
+
+pub type SmtlibError = ParseError<SmtlibErrorKind>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmtlibErrorKind {
+    UnexpectedEof,
+    Expected(&'static str),
+    UnknownCommand(String),
+    UnknownOperator(String),
+    InvalidNumber(String),
+    InvalidConstant(String),
+    MisplacedCommand(&'static str),
+}
+
+impl fmt::Display for SmtlibErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::SmtlibErrorKind::*;
+        match self {
+            UnexpectedEof => write!(f, "unexpected end of input"),
+            Expected(token) => write!(f, "expected `{}`", token),
+            UnknownCommand(name) => write!(f, "unknown command `{}`", name),
+            UnknownOperator(name) => write!(f, "unknown operator `{}`", name),
+            InvalidNumber(token) => write!(f, "invalid number `{}`", token),
+            InvalidConstant(token) => write!(f, "invalid constant `{}`", token),
+            MisplacedCommand(name) => write!(
+                f,
+                "misplaced `{}`, only `(check-sat)` and then `(get-unsat-core)` may end the script",
+                name
+            ),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Problem, SmtlibError> {
     let mut parser = Parser::new(input);
     parser.parse_problem()
 }
@@ -18,8 +54,11 @@ impl Parser {
         }
     }
 
-    fn parse_problem(&mut self) -> Problem {
+    // nothing acts on the commands as they come, so `check-sat` and
+    // `get-unsat-core` are only accepted in this order at the end
+    fn parse_problem(&mut self) -> Result<Problem, SmtlibError> {
         let mut problem = Vec::new();
+        let mut last = None;
 
         loop {
             self.consume_whitespace();
@@ -27,164 +66,254 @@ impl Parser {
                 break;
             }
 
-            problem.push(self.parse_statement());
+            let start = self.pos + 1;
+            let stmt = self.parse_statement()?;
+            last = match (&stmt, last) {
+                (Statement::CheckSat, None) => Some(("check-sat", start)),
+                (Statement::GetUnsatCore, Some(("check-sat", _))) => {
+                    Some(("get-unsat-core", start))
+                }
+                (Statement::GetUnsatCore, None) => {
+                    let kind = SmtlibErrorKind::MisplacedCommand("get-unsat-core");
+                    return Err(self.error_at(start, kind));
+                }
+                (_, Some((name, pos))) => {
+                    let kind = SmtlibErrorKind::MisplacedCommand(name);
+                    return Err(self.error_at(pos, kind));
+                }
+                (_, None) => None,
+            };
+            problem.push(stmt);
         }
 
-        problem
+        Ok(problem)
     }
 
-    fn parse_statement(&mut self) -> Statement {
-        assert_eq!(self.consume_char(), '(');
+    fn parse_statement(&mut self) -> Result<Statement, SmtlibError> {
+        self.expect("(")?;
 
+        let start = self.pos;
         let stmt = match self.consume_string().as_str() {
-            "assert" => Statement::Assert(self.parse_expression()),
+            "assert" => {
+                self.consume_whitespace();
+                match self.try_consume_named() {
+                    true => self.parse_named_assertion()?,
+                    false => Statement::Assert(self.parse_expression()?),
+                }
+            }
+            "check-sat" => Statement::CheckSat,
             "declare-fun" => {
                 self.consume_whitespace();
                 let name = self.consume_string();
 
                 self.consume_whitespace();
-                assert_eq!(self.consume_char(), '(');
-                assert_eq!(self.consume_char(), ')');
+                self.expect("(")?;
+                self.consume_whitespace();
+                self.expect(")")?;
 
                 self.consume_whitespace();
-                Statement::Declare(name, self.parse_variable_type())
+                Statement::Declare(name, self.parse_variable_type()?)
+            }
+            "get-unsat-core" => Statement::GetUnsatCore,
+            name => {
+                let kind = SmtlibErrorKind::UnknownCommand(name.into());
+                return Err(self.error_at(start, kind));
             }
-            _ => panic!(),
         };
 
         self.consume_whitespace();
-        assert_eq!(self.consume_char(), ')');
+        self.expect(")")?;
 
-        stmt
+        Ok(stmt)
     }
 
-    fn parse_variable_type(&mut self) -> VariableType {
+    // consumes the `( !` of a named assertion, with any whitespace between
+    // the two tokens, or nothing if the assertion is not named
+    fn try_consume_named(&mut self) -> bool {
+        let start = self.pos;
+        if self.try_consume("(") {
+            self.consume_whitespace();
+            if self.try_consume("!") {
+                return true;
+            }
+        }
+        self.pos = start;
+        false
+    }
+
+    // the rest of `(! expr :named name)`, whose name the unsat cores refer to
+    fn parse_named_assertion(&mut self) -> Result<Statement, SmtlibError> {
+        let expr = self.parse_expression()?;
+
+        self.consume_whitespace();
+        self.expect(":named")?;
+
+        self.consume_whitespace();
+        let name = self.consume_string();
+        if name.is_empty() {
+            return Err(self.error(SmtlibErrorKind::Expected("<name>")));
+        }
+
+        self.consume_whitespace();
+        self.expect(")")?;
+
+        Ok(Statement::AssertNamed(name, expr))
+    }
+
+    fn parse_variable_type(&mut self) -> Result<VariableType, SmtlibError> {
         if self.try_consume("Bool") {
-            return VariableType::Bool;
+            return Ok(VariableType::Bool);
         }
 
-        assert!(self.try_consume("(_ BitVec "));
+        self.expect("(_ BitVec ")?;
 
-        let len = self.consume_number();
-        assert_eq!(self.consume_char(), ')');
+        let len = self.consume_number()?;
+        self.expect(")")?;
 
-        VariableType::BitVector(len)
+        Ok(VariableType::BitVector(len))
     }
 
-    fn parse_expression(&mut self) -> Expression {
+    fn parse_expression(&mut self) -> Result<Expression, SmtlibError> {
         self.consume_whitespace();
 
-        match self.peek_char() {
+        match self.peek_char()? {
             '#' => {
                 return self.parse_constant();
             }
             c if c.is_ascii_alphabetic() => {
                 let name = self.consume_string();
-                return Expression::Variable(name);
+                return Ok(Expression::Variable(name));
             }
             _ => {}
         }
 
-        assert_eq!(self.consume_char(), '(');
+        self.expect("(")?;
 
-        if self.peek_char() == '=' {
-            assert_eq!(self.consume_char(), '=');
+        if self.try_consume("=") {
             let expr = Expression::Eq(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             );
-            assert_eq!(self.consume_char(), ')');
-            return expr;
+            self.consume_whitespace();
+            self.expect(")")?;
+            return Ok(expr);
         }
 
+        let start = self.pos;
         let op = self.consume_string();
         let expr = match op.as_str() {
-            "not" => Expression::Not(Box::new(self.parse_expression())),
+            "not" => Expression::Not(Box::new(self.parse_expression()?)),
             "and" => Expression::And(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "or" => Expression::Or(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "xor" => Expression::Xor(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
 
-            "bvnot" => Expression::BvNot(Box::new(self.parse_expression())),
+            "bvnot" => Expression::BvNot(Box::new(self.parse_expression()?)),
             "bvand" => Expression::BvAnd(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvor" => Expression::BvOr(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvxor" => Expression::BvXor(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvadd" => Expression::BvAdd(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvsub" => Expression::BvSub(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvmul" => Expression::BvMul(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
-            "bvshl" => Expression::BvShl(Box::new(self.parse_expression()), self.consume_number()),
-            "bvshr" => Expression::BvShr(Box::new(self.parse_expression()), self.consume_number()),
+            "bvshl" => {
+                Expression::BvShl(Box::new(self.parse_expression()?), self.consume_number()?)
+            }
+            "bvshr" => {
+                Expression::BvShr(Box::new(self.parse_expression()?), self.consume_number()?)
+            }
             "bvult" => Expression::BvUlt(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvule" => Expression::BvUle(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvugt" => Expression::BvUgt(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
             "bvuge" => Expression::BvUge(
-                Box::new(self.parse_expression()),
-                Box::new(self.parse_expression()),
+                Box::new(self.parse_expression()?),
+                Box::new(self.parse_expression()?),
             ),
-            _ => panic!(),
+            _ => return Err(self.error_at(start, SmtlibErrorKind::UnknownOperator(op))),
         };
 
-        assert_eq!(self.consume_char(), ')');
+        self.consume_whitespace();
+        self.expect(")")?;
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_constant(&mut self) -> Expression {
-        assert_eq!(self.consume_char(), '#');
+    fn parse_constant(&mut self) -> Result<Expression, SmtlibError> {
+        let start = self.pos;
+        self.expect("#")?;
 
-        match self.consume_char() {
+        let (literal, radix, bits) = match self.peek_char()? {
             'b' => {
-                let literal = self.consume_while(|c| matches!(c, '0' | '1'));
-                let value = usize::from_str_radix(&literal, 2).unwrap();
-                Expression::Constant(value, literal.len())
+                self.consume_char()?;
+                (self.consume_while(|c| matches!(c, '0' | '1')), 2, 1)
             }
             'x' => {
-                let literal = self.consume_while(|c| matches!(c, '0'..='9' | 'a'..='f'));
-                let value = usize::from_str_radix(&literal, 16).unwrap();
-                Expression::Constant(value, literal.len() * 4)
+                self.consume_char()?;
+                (
+                    self.consume_while(|c| matches!(c, '0'..='9' | 'a'..='f')),
+                    16,
+                    4,
+                )
+            }
+            _ => (String::new(), 2, 1),
+        };
+        let value = usize::from_str_radix(&literal, radix).ok();
+        let delimited = self.input[self.pos..]
+            .chars()
+            .next()
+            .is_none_or(|c| c.is_ascii_whitespace() || matches!(c, '(' | ')'));
+        match value {
+            Some(value) if delimited => Ok(Expression::Constant(value, literal.len() * bits)),
+            _ => {
+                // the whole token, up to the next delimiter
+                self.consume_while(|c| !c.is_ascii_whitespace() && !matches!(c, '(' | ')'));
+                let token = self.input[start..self.pos].to_string();
+                Err(self.error_at(start, SmtlibErrorKind::InvalidConstant(token)))
             }
-            _ => panic!(),
         }
     }
 
-    fn consume_number(&mut self) -> usize {
+    fn consume_number(&mut self) -> Result<usize, SmtlibError> {
+        self.consume_whitespace();
+        let start = self.pos;
         let int_literal = self.consume_while(|c| c.is_ascii_digit());
-        int_literal.parse().unwrap()
+        int_literal
+            .parse()
+            .map_err(|_| self.error_at(start, SmtlibErrorKind::InvalidNumber(int_literal)))
     }
 
     fn consume_string(&mut self) -> String {
@@ -200,10 +329,11 @@ impl Parser {
         F: Fn(char) -> bool,
     {
         let mut result = String::new();
-        while !self.is_eof() && test(self.peek_char()) {
-            result.push(self.consume_char());
+        while let Some(c) = self.input[self.pos..].chars().next().filter(|&c| test(c)) {
+            result.push(c);
+            self.pos += c.len_utf8();
         }
-        return result;
+        result
     }
 
     fn try_consume(&mut self, s: &str) -> bool {
@@ -216,6 +346,14 @@ impl Parser {
         false
     }
 
+    fn expect(&mut self, s: &'static str) -> Result<(), SmtlibError> {
+        match self.try_consume(s) {
+            true => Ok(()),
+            false if self.is_eof() => Err(self.error(SmtlibErrorKind::UnexpectedEof)),
+            false => Err(self.error(SmtlibErrorKind::Expected(s))),
+        }
+    }
+
     fn is_eof(&self) -> bool {
         self.pos >= self.input.len()
     }
@@ -224,13 +362,31 @@ impl Parser {
         self.input[self.pos..].starts_with(s)
     }
 
-    fn peek_char(&mut self) -> char {
-        self.input[self.pos..].chars().next().unwrap()
+    fn peek_char(&mut self) -> Result<char, SmtlibError> {
+        self.input[self.pos..]
+            .chars()
+            .next()
+            .ok_or_else(|| self.error(SmtlibErrorKind::UnexpectedEof))
     }
 
-    fn consume_char(&mut self) -> char {
-        let cur_char = self.peek_char();
-        self.pos += 1;
-        cur_char
+    fn consume_char(&mut self) -> Result<char, SmtlibError> {
+        let cur_char = self.peek_char()?;
+        self.pos += cur_char.len_utf8();
+        Ok(cur_char)
+    }
+
+    fn error(&self, kind: SmtlibErrorKind) -> SmtlibError {
+        self.error_at(self.pos, kind)
+    }
+
+    // the 1-based line and column of the byte `pos`
+    fn error_at(&self, pos: usize, kind: SmtlibErrorKind) -> SmtlibError {
+        let before = &self.input[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        SmtlibError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
     }
 }
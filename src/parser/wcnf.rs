@@ -0,0 +1,177 @@
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
+
+use crate::{maxsat, sat::types::*};
+
+use super::{
+    error::ParseError,
+    lines::{read_lines, tokenize},
+};
+
+/// A weighted partial MaxSAT problem, read from either the WCNF format of the
+/// MaxSAT Evaluations up to 2021 (`p wcnf` header, hard clauses weighted with
+/// `top`) or the one since 2022 (no header, hard clauses marked with `h`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Wcnf {
+    /// The declared number of variables, raised to the highest one used.
+    pub num_vars: usize,
+    pub hard: Vec<Vec<Literal>>,
+    pub soft: Vec<(Vec<Literal>, u64)>,
+}
+
+impl Wcnf {
+    pub fn add_to(&self, solver: &mut maxsat::Solver) {
+        solver.reserve_variables(self.num_vars);
+        for clause in &self.hard {
+            solver.add_clause(clause);
+        }
+        for (clause, weight) in &self.soft {
+            solver.add_soft_clause(clause, *weight);
+        }
+    }
+}
+
+pub type WcnfError = ParseError<WcnfErrorKind>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WcnfErrorKind {
+    InvalidHeader,
+    DuplicateHeader,
+    InvalidWeight(String),
+    WeightOverflow,
+    InvalidLiteral(String),
+    UnterminatedClause,
+    Io(String),
+}
+
+impl fmt::Display for WcnfErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::WcnfErrorKind::*;
+        match self {
+            InvalidHeader => write!(f, "expected `p wcnf <variables> <clauses> [<top>]`"),
+            DuplicateHeader => write!(f, "duplicate `p wcnf` header"),
+            InvalidWeight(token) => write!(f, "invalid weight `{}`", token),
+            WeightOverflow => write!(f, "the soft clauses weigh more than {} in total", u64::MAX),
+            InvalidLiteral(token) => write!(f, "invalid literal `{}`", token),
+            UnterminatedClause => write!(f, "clause is not terminated by 0"),
+            Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for WcnfErrorKind {
+    fn from(err: io::Error) -> Self {
+        WcnfErrorKind::Io(err.to_string())
+    }
+}
+
+pub fn parse(input: &str) -> Result<Wcnf, WcnfError> {
+    read(input.as_bytes())
+}
+
+pub fn read<R: BufRead>(reader: R) -> Result<Wcnf, WcnfError> {
+    let mut parser = Parser::default();
+    read_lines(reader, |line| parser.parse_line(line).map(|()| true))?;
+    parser.finish()
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    // the `top` weight of the header, if there is one
+    header: Option<Option<u64>>,
+    wcnf: Wcnf,
+    // the clause being read and its weight, None if hard
+    clause: Option<(Option<u64>, Vec<Literal>)>,
+    // the weight of the soft clauses so far
+    total: u64,
+}
+
+impl Parser {
+    fn parse_line(&mut self, line: &str) -> Result<(), WcnfError> {
+        self.line += 1;
+
+        let mut tokens = tokenize(line).peekable();
+        match tokens.peek() {
+            None => return Ok(()),
+            Some((_, token)) if token.starts_with('c') => return Ok(()),
+            Some(&(column, "p")) => {
+                if self.header.is_some() {
+                    return Err(self.error(column, WcnfErrorKind::DuplicateHeader));
+                }
+                let header = tokens.map(|(_, t)| t).collect::<Vec<_>>();
+                let (vars, top) = match header.as_slice() {
+                    ["p", "wcnf", vars, _] => (vars.parse().ok(), Some(None)),
+                    ["p", "wcnf", vars, _, top] => (vars.parse().ok(), top.parse().ok().map(Some)),
+                    _ => (None, None),
+                };
+                match (vars, top) {
+                    (Some(vars), Some(top)) => {
+                        self.wcnf.num_vars = self.wcnf.num_vars.max(vars);
+                        self.header = Some(top);
+                    }
+                    _ => return Err(self.error(column, WcnfErrorKind::InvalidHeader)),
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        for (column, token) in tokens {
+            if self.clause.is_none() {
+                let weight = match token {
+                    "h" => None,
+                    _ => {
+                        let weight = token.parse::<u64>().map_err(|_| {
+                            self.error(column, WcnfErrorKind::InvalidWeight(token.into()))
+                        })?;
+                        match self.header {
+                            Some(Some(top)) if weight >= top => None,
+                            _ => {
+                                self.total = self.total.checked_add(weight).ok_or_else(|| {
+                                    self.error(column, WcnfErrorKind::WeightOverflow)
+                                })?;
+                                Some(weight)
+                            }
+                        }
+                    }
+                };
+                self.clause = Some((weight, Vec::new()));
+                continue;
+            }
+
+            let value = token
+                .parse::<isize>()
+                .map_err(|_| self.error(column, WcnfErrorKind::InvalidLiteral(token.into())))?;
+            if value != 0 {
+                let literal = Literal::new(value.unsigned_abs(), value < 0);
+                self.wcnf.num_vars = self.wcnf.num_vars.max(literal.var);
+                self.clause.as_mut().unwrap().1.push(literal);
+                continue;
+            }
+
+            match self.clause.take().unwrap() {
+                (None, literals) => self.wcnf.hard.push(literals),
+                (Some(weight), literals) => self.wcnf.soft.push((literals, weight)),
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Wcnf, WcnfError> {
+        if self.clause.is_some() {
+            return Err(self.error(1, WcnfErrorKind::UnterminatedClause));
+        }
+        Ok(self.wcnf)
+    }
+
+    fn error(&self, column: usize, kind: WcnfErrorKind) -> WcnfError {
+        WcnfError {
+            line: self.line.max(1),
+            column,
+            kind,
+        }
+    }
+}
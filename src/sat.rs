@@ -1,5 +1,55 @@
+pub mod config;
+pub use config::*;
+
+pub mod count;
+pub use count::*;
+
+mod encoding;
+
+pub mod enumerate;
+pub use enumerate::*;
+
+pub mod heuristic;
+pub use heuristic::*;
+
+pub mod inprocess;
+pub use inprocess::*;
+
+pub mod interrupt;
+pub use interrupt::*;
+
+pub mod limits;
+pub use limits::*;
+
+pub mod mus;
+pub use mus::*;
+
+pub mod pb;
+pub use pb::*;
+
+pub mod preprocess;
+pub use preprocess::*;
+
+pub mod proof;
+pub use proof::*;
+
+pub mod reduce;
+pub use reduce::*;
+
+pub mod restart;
+pub use restart::*;
+
+pub mod result;
+pub use result::*;
+
 pub mod solver;
 pub use solver::*;
 
+pub mod stats;
+pub use stats::*;
+
 pub mod types;
 pub use types::*;
+
+pub mod xor;
+pub use xor::*;
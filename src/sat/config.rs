@@ -0,0 +1,14 @@
+use super::{
+    inprocess::InprocessConfig, pb::PbEncoding, preprocess::PreprocessConfig, reduce::ReduceConfig,
+    restart::RestartStrategy, xor::XorConfig,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct SolverConfig {
+    pub restart: RestartStrategy,
+    pub reduce: ReduceConfig,
+    pub preprocess: PreprocessConfig,
+    pub inprocess: InprocessConfig,
+    pub xor: XorConfig,
+    pub pb: PbEncoding,
+}
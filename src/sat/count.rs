@@ -0,0 +1,10 @@
+pub mod approx;
+pub use approx::*;
+
+pub mod counter;
+pub use counter::*;
+
+mod exact;
+
+pub mod natural;
+pub use natural::*;
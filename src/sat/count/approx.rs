@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use crate::sat::{
+    result::SolveResult,
+    types::{Literal, Variable},
+};
+
+use super::{counter::ModelCounter, natural::Natural};
+
+/// Parameters of the approximate counter: with probability at least
+/// `1 - delta`, its estimate is within a factor of `1 + epsilon` of the
+/// count.
+#[derive(Debug, Clone, Copy)]
+pub struct ApproxConfig {
+    pub epsilon: f64,
+    pub delta: f64,
+    pub seed: u64,
+}
+
+impl Default for ApproxConfig {
+    fn default() -> Self {
+        Self {
+            epsilon: 0.8,
+            delta: 0.2,
+            seed: 1,
+        }
+    }
+}
+
+/// Why [`ModelCounter::approx_count`] returned no estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApproxError {
+    /// The interrupt handle stopped the count.
+    Interrupted,
+    /// No round found a cell with fewer models than the threshold, which
+    /// leaves only the exact count.
+    NoEstimate,
+}
+
+impl ModelCounter {
+    /// Estimates the number of models by splitting them into cells with
+    /// random XOR constraints and counting the models of a single cell.
+    pub fn approx_count(&self, config: &ApproxConfig) -> Result<Natural, ApproxError> {
+        let epsilon = config.epsilon;
+        let threshold = (1.0
+            + 9.84 * (1.0 + epsilon / (1.0 + epsilon)) * (1.0 + 1.0 / epsilon).powi(2))
+        .ceil() as usize;
+        let iterations = (17.0 * (3.0 / config.delta).log2()).ceil() as usize;
+        let vars = self.projected_vars();
+
+        // small counts are found by enumeration
+        let mut solver = self.solver();
+        let mut models = solver.enumerate_models(&vars, Some(threshold));
+        let n = models.by_ref().count();
+        if let Some(SolveResult::Unknown(_)) = models.result() {
+            return Err(ApproxError::Interrupted);
+        }
+        if n < threshold {
+            return Ok(Natural::from(n as u64));
+        }
+
+        let mut rng = Rng(config.seed.max(1));
+        let mut estimates = Vec::new();
+        let mut hint = None;
+        for _ in 0..iterations {
+            if let Some((m, estimate)) = self.estimate(&vars, threshold, hint, &mut rng)? {
+                estimates.push(estimate);
+                hint = Some(m);
+            }
+        }
+        if estimates.is_empty() {
+            return Err(ApproxError::NoEstimate);
+        }
+        estimates.sort();
+        Ok(estimates.swap_remove(estimates.len() / 2))
+    }
+
+    // ref: Chakraborty, Meel and Vardi, Algorithmic Improvements in Approximate Counting (2016)
+    // the least number of XORs whose cell is below `threshold`, searched from `hint`, with the cell
+    fn estimate(
+        &self,
+        vars: &[Variable],
+        threshold: usize,
+        hint: Option<usize>,
+        rng: &mut Rng,
+    ) -> Result<Option<(usize, Natural)>, ApproxError> {
+        let mut solver = self.solver();
+        let mut selectors = Vec::new();
+        for _ in 0..vars.len() {
+            let selector = solver.new_literal();
+            let mut literals = vars
+                .iter()
+                .filter(|_| rng.next() & 1 == 1)
+                .map(|&var| Literal::new(var, false))
+                .collect::<Vec<_>>();
+            literals.push(selector);
+            solver.add_xor(&literals, rng.next() & 1 == 1);
+            selectors.push(-selector);
+        }
+
+        // cells only shrink with more XORs, so m can be found by binary search;
+        // once interrupted, every cell counts as empty to end the search
+        let mut counts = HashMap::new();
+        let mut interrupted = false;
+        let mut cell = |m: usize| {
+            if interrupted {
+                return 0;
+            }
+            *counts.entry(m).or_insert_with(|| {
+                let mut models = solver.enumerate_models_with_assumptions(
+                    vars,
+                    &selectors[..m],
+                    Some(threshold),
+                );
+                let n = models.by_ref().count();
+                interrupted = matches!(models.result(), Some(SolveResult::Unknown(_)));
+                n
+            })
+        };
+        let n = vars.len();
+        let m = match hint {
+            Some(mut m) if cell(m) < threshold => {
+                while m > 1 && cell(m - 1) < threshold {
+                    m -= 1;
+                }
+                m
+            }
+            Some(mut m) => {
+                while m < n && cell(m) >= threshold {
+                    m += 1;
+                }
+                m
+            }
+            None => {
+                let (mut low, mut high) = (1, n);
+                while low < high {
+                    let m = (low + high) / 2;
+                    match cell(m) < threshold {
+                        true => high = m,
+                        false => low = m + 1,
+                    }
+                }
+                low
+            }
+        };
+        // even all the XORs may leave too many models
+        let count = cell(m);
+        if interrupted {
+            return Err(ApproxError::Interrupted);
+        }
+        Ok(match count < threshold {
+            true => Some((m, Natural::from(count as u64) << m)),
+            false => None,
+        })
+    }
+}
+
+// xorshift64
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
@@ -0,0 +1,68 @@
+use crate::sat::{interrupt::InterruptHandle, solver::Solver, types::*};
+
+/// A formula whose models are counted, optionally projected onto some of its
+/// variables: models differing only outside the projection count once.
+#[derive(Debug, Clone, Default)]
+pub struct ModelCounter {
+    pub(crate) num_vars: usize,
+    pub(crate) clauses: Vec<Vec<Literal>>,
+    pub(crate) projection: Option<Vec<Variable>>,
+    pub(crate) interrupt: InterruptHandle,
+}
+
+impl ModelCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the variables up to `num_vars`, which count even when they
+    /// occur in no clause.
+    pub fn reserve_variables(&mut self, num_vars: usize) {
+        self.num_vars = self.num_vars.max(num_vars);
+    }
+
+    pub fn add_clause(&mut self, literals: &[Literal]) {
+        for literal in literals {
+            self.reserve_variables(literal.var);
+        }
+        self.clauses.push(literals.to_vec());
+    }
+
+    /// Adds `vars` to the projection. Without a projection all variables are
+    /// counted.
+    pub fn add_projection(&mut self, vars: &[Variable]) {
+        for &var in vars {
+            self.reserve_variables(var);
+        }
+        self.projection.get_or_insert_with(Vec::new).extend(vars);
+    }
+
+    /// Stops a running `count` or `approx_count`, which then returns None.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    pub fn is_projected(&self) -> bool {
+        self.projection.is_some()
+    }
+
+    pub(crate) fn projected_vars(&self) -> Vec<Variable> {
+        let mut vars = match &self.projection {
+            Some(projection) => projection.clone(),
+            None => (1..=self.num_vars).collect(),
+        };
+        vars.sort_unstable();
+        vars.dedup();
+        vars
+    }
+
+    pub(crate) fn solver(&self) -> Solver {
+        let mut solver = Solver::new();
+        solver.interrupt = self.interrupt.clone();
+        solver.reserve_variables(self.num_vars);
+        for clause in &self.clauses {
+            solver.add_clause(clause);
+        }
+        solver
+    }
+}
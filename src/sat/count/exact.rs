@@ -0,0 +1,243 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::sat::{result::SolveResult, solver::Solver, types::*};
+
+use super::{counter::ModelCounter, natural::Natural};
+
+impl ModelCounter {
+    /// Counts the models exactly. The search splits the formula into
+    /// components without common variables, counts them one by one and
+    /// caches their counts. Returns None if interrupted.
+    pub fn count(&self) -> Option<Natural> {
+        let vars = self.projected_vars();
+        let mut projected = vec![false; self.num_vars + 1];
+        for &var in &vars {
+            projected[var] = true;
+        }
+
+        let mut originals = Vec::with_capacity(self.clauses.len());
+        for clause in &self.clauses {
+            let mut clause = clause.clone();
+            clause.sort_unstable_by_key(|l| l.index());
+            clause.dedup();
+            if !clause.windows(2).any(|w| w[0].var == w[1].var) {
+                originals.push(clause);
+            }
+        }
+
+        // one group per clause, so that a check only enables the clauses of
+        // the component at hand
+        let mut solver = Solver::new();
+        solver.interrupt = self.interrupt.clone();
+        solver.reserve_variables(self.num_vars);
+        let groups = originals
+            .iter()
+            .map(|clause| {
+                let group = solver.new_group();
+                solver.add_group_clause(group, clause);
+                group
+            })
+            .collect();
+
+        let clauses = originals.iter().cloned().enumerate().collect::<Clauses>();
+        let mut search = Search {
+            projected,
+            cache: HashMap::new(),
+            solver,
+            groups,
+            originals,
+        };
+        search.count(&clauses, None, &vars)
+    }
+}
+
+// the remaining literals of a clause, tagged with the index of the clause
+// they are left of
+type Clauses = Vec<(usize, Vec<Literal>)>;
+
+// ref: T. Sang et al., Combining Component Caching and Clause Learning for
+// Effective Model Counting (2004), without the clause learning.
+struct Search {
+    projected: Vec<bool>,
+    // counts of the components seen so far, keyed by their clauses
+    cache: HashMap<Clauses, Natural>,
+    solver: Solver,
+    groups: Vec<Literal>,
+    originals: Vec<Vec<Literal>>,
+}
+
+impl Search {
+    // the assignments to `vars`, which include the projected variables of
+    // `clauses`, that extend to a model after assigning `decision`
+    fn count(
+        &mut self,
+        clauses: &[(usize, Vec<Literal>)],
+        decision: Option<Literal>,
+        vars: &[Variable],
+    ) -> Option<Natural> {
+        let Some((clauses, assigned)) = propagate(clauses, decision) else {
+            return Some(Natural::zero());
+        };
+        let occurring = clauses
+            .iter()
+            .flat_map(|(_, clause)| clause)
+            .map(|l| l.var)
+            .collect::<HashSet<_>>();
+        let free = vars
+            .iter()
+            .filter(|var| !assigned.contains_key(var) && !occurring.contains(var))
+            .count();
+
+        let mut count = Natural::power_of_two(free);
+        for component in components(clauses) {
+            let n = self.count_component(component)?;
+            if n.is_zero() {
+                return Some(n);
+            }
+            count = &count * &n;
+        }
+        Some(count)
+    }
+
+    fn count_component(&mut self, clauses: Clauses) -> Option<Natural> {
+        if let Some(count) = self.cache.get(&clauses) {
+            return Some(count.clone());
+        }
+        if self.solver.interrupt.is_interrupted() {
+            self.solver.interrupt.clear();
+            return None;
+        }
+
+        let mut occurrences = HashMap::<Variable, usize>::new();
+        for literal in clauses.iter().flat_map(|(_, clause)| clause) {
+            if self.projected[literal.var] {
+                *occurrences.entry(literal.var).or_default() += 1;
+            }
+        }
+        // branch on the projected variable occurring most often; without one
+        // the component only needs to be satisfiable
+        let branch = occurrences
+            .iter()
+            .max_by_key(|&(&var, &n)| (n, Reverse(var)))
+            .map(|(&var, _)| var);
+        let count = match branch {
+            None => match self.satisfiable(&clauses)? {
+                true => Natural::one(),
+                false => Natural::zero(),
+            },
+            Some(var) => {
+                let vars = occurrences.keys().cloned().collect::<Vec<_>>();
+                let mut count = Natural::zero();
+                for inverted in [false, true] {
+                    let decision = Some(Literal::new(var, inverted));
+                    count = &count + &self.count(&clauses, decision, &vars)?;
+                }
+                count
+            }
+        };
+        self.cache.insert(clauses, count.clone());
+        Some(count)
+    }
+
+    // assumes the groups of the clauses together with the negations of the
+    // literals they lost, which are false under the current assignment.
+    // None if interrupted
+    fn satisfiable(&mut self, clauses: &Clauses) -> Option<bool> {
+        let mut assumptions = Vec::new();
+        for (index, clause) in clauses {
+            assumptions.push(self.groups[*index]);
+            for literal in &self.originals[*index] {
+                if !clause.contains(literal) {
+                    assumptions.push(-*literal);
+                }
+            }
+        }
+        match self.solver.check_with_assumptions(&assumptions) {
+            SolveResult::Sat => Some(true),
+            SolveResult::Unsat => Some(false),
+            SolveResult::Unknown(_) => None,
+        }
+    }
+}
+
+// assigns `decision` and applies the unit clauses until none are left,
+// returning the remaining clauses and the assigned variables, or None on a
+// conflict
+fn propagate(
+    clauses: &[(usize, Vec<Literal>)],
+    decision: Option<Literal>,
+) -> Option<(Clauses, HashMap<Variable, bool>)> {
+    let mut assigned = HashMap::new();
+    if let Some(literal) = decision {
+        assigned.insert(literal.var, !literal.inverted);
+    }
+    let mut clauses = simplify(clauses, &assigned);
+    loop {
+        let mut units = false;
+        for (_, clause) in &clauses {
+            match clause.as_slice() {
+                [] => return None,
+                [literal] => match assigned.insert(literal.var, !literal.inverted) {
+                    Some(value) if value == literal.inverted => return None,
+                    _ => units = true,
+                },
+                _ => {}
+            }
+        }
+        if !units {
+            return Some((clauses, assigned));
+        }
+        clauses = simplify(&clauses, &assigned);
+    }
+}
+
+// drops the satisfied clauses and the false literals of the others
+fn simplify(clauses: &[(usize, Vec<Literal>)], assigned: &HashMap<Variable, bool>) -> Clauses {
+    let value = |l: &Literal| assigned.get(&l.var).map(|&v| v != l.inverted);
+    clauses
+        .iter()
+        .filter(|(_, clause)| !clause.iter().any(|l| value(l) == Some(true)))
+        .map(|(index, clause)| {
+            let clause = clause.iter().filter(|l| value(l).is_none()).cloned();
+            (*index, clause.collect())
+        })
+        .collect()
+}
+
+// splits the clauses into groups without common variables, each sorted so
+// that equal components are equal keys of the cache
+fn components(clauses: Clauses) -> Vec<Clauses> {
+    let mut parents = HashMap::<Variable, Variable>::new();
+    fn find(parents: &mut HashMap<Variable, Variable>, var: Variable) -> Variable {
+        let parent = *parents.entry(var).or_insert(var);
+        if parent == var {
+            return var;
+        }
+        let root = find(parents, parent);
+        parents.insert(var, root);
+        root
+    }
+    for (_, clause) in &clauses {
+        let first = find(&mut parents, clause[0].var);
+        for literal in &clause[1..] {
+            let root = find(&mut parents, literal.var);
+            parents.insert(root, first);
+        }
+    }
+
+    let mut groups = HashMap::<Variable, Clauses>::new();
+    for clause in clauses {
+        let root = find(&mut parents, clause.1[0].var);
+        groups.entry(root).or_default().push(clause);
+    }
+    let mut components = groups.into_values().collect::<Vec<_>>();
+    for component in &mut components {
+        component.sort_by_cached_key(|(index, clause)| {
+            (clause.iter().map(|l| l.index()).collect::<Vec<_>>(), *index)
+        });
+    }
+    components
+}
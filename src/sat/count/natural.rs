@@ -0,0 +1,199 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Shl},
+};
+
+/// An arbitrary-precision natural number, as model counts easily exceed any
+/// machine integer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Natural {
+    // base 2^32 digits from the least significant, without leading zeros
+    limbs: Vec<u32>,
+}
+
+impl Natural {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// 2 to the power of `exponent`.
+    pub fn power_of_two(exponent: usize) -> Self {
+        Self::one() << exponent
+    }
+
+    /// The base 10 logarithm, precise to the digits of an `f64`.
+    pub fn log10(&self) -> f64 {
+        let bits = self.limbs.len() * 32;
+        // the top 64 bits and the number of bits below them
+        let (top, shift) = match bits {
+            0 => return f64::NEG_INFINITY,
+            32 => (self.limbs[0] as u64, 0),
+            _ => {
+                let n = self.limbs.len();
+                let top = (self.limbs[n - 1] as u64) << 32 | self.limbs[n - 2] as u64;
+                (top, bits - 64)
+            }
+        };
+        (top as f64).log10() + shift as f64 * 2f64.log10()
+    }
+
+    // divides by a small divisor in place and returns the remainder
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut rem = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let value = rem << 32 | *limb as u64;
+            *limb = (value / divisor as u64) as u32;
+            rem = value % divisor as u64;
+        }
+        self.normalize();
+        rem as u32
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for Natural {
+    fn from(value: u64) -> Self {
+        let mut n = Self {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        };
+        n.normalize();
+        n
+    }
+}
+
+impl Add for &Natural {
+    type Output = Natural;
+
+    fn add(self, other: &Natural) -> Natural {
+        let (long, short) = match self.limbs.len() >= other.limbs.len() {
+            true => (&self.limbs, &other.limbs),
+            false => (&other.limbs, &self.limbs),
+        };
+        let mut limbs = Vec::with_capacity(long.len() + 1);
+        let mut carry = 0u64;
+        for (i, &limb) in long.iter().enumerate() {
+            let sum = limb as u64 + short.get(i).cloned().unwrap_or(0) as u64 + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        Natural { limbs }
+    }
+}
+
+impl Add for Natural {
+    type Output = Natural;
+
+    fn add(self, other: Natural) -> Natural {
+        &self + &other
+    }
+}
+
+impl Mul for &Natural {
+    type Output = Natural;
+
+    fn mul(self, other: &Natural) -> Natural {
+        if self.is_zero() || other.is_zero() {
+            return Natural::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let value = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = value as u32;
+                carry = value >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        let mut n = Natural { limbs };
+        n.normalize();
+        n
+    }
+}
+
+impl Mul for Natural {
+    type Output = Natural;
+
+    fn mul(self, other: Natural) -> Natural {
+        &self * &other
+    }
+}
+
+impl Shl<usize> for Natural {
+    type Output = Natural;
+
+    fn shl(self, shift: usize) -> Natural {
+        if self.is_zero() {
+            return self;
+        }
+        let (words, bits) = (shift / 32, shift % 32);
+        let mut limbs = vec![0u32; words];
+        let mut carry = 0u32;
+        for &limb in &self.limbs {
+            match bits {
+                0 => limbs.push(limb),
+                _ => {
+                    limbs.push(limb << bits | carry);
+                    carry = limb >> (32 - bits);
+                }
+            }
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+        Natural { limbs }
+    }
+}
+
+impl Ord for Natural {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for Natural {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Natural {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // nine decimal digits at a time, from the least significant
+        let mut n = self.clone();
+        let mut chunks = Vec::new();
+        while !n.is_zero() {
+            chunks.push(n.div_rem_small(1_000_000_000));
+        }
+        match chunks.pop() {
+            None => write!(f, "0"),
+            Some(first) => {
+                write!(f, "{}", first)?;
+                for chunk in chunks.iter().rev() {
+                    write!(f, "{:09}", chunk)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::{pb::PbEncoding, solver::Solver, types::Literal};
+
+// Clausal encodings of cardinality and pseudo-Boolean constraints. They only
+// need to propagate towards the bound, so most of them define their
+// auxiliary variables in one direction.
+impl Solver {
+    /// Adds clauses allowing at most `k` of `literals`, `k < literals.len()`.
+    pub(crate) fn encode_at_most(&mut self, literals: &[Literal], k: usize, encoding: PbEncoding) {
+        if k == 0 {
+            for &literal in literals {
+                self.add_clause(&[-literal]);
+            }
+            return;
+        }
+        match encoding {
+            PbEncoding::SequentialCounter => self.sequential_counter(literals, k),
+            PbEncoding::Native | PbEncoding::Totalizer => {
+                let outputs = self.totalizer(literals, k + 1);
+                self.add_clause(&[-outputs[k]]);
+            }
+            PbEncoding::CardinalityNetwork => {
+                let m = (k + 1).next_power_of_two();
+                let mut inputs = literals.to_vec();
+                if !inputs.len().is_multiple_of(m) {
+                    let constant = self.false_literal();
+                    inputs.resize(inputs.len().next_multiple_of(m), constant);
+                }
+                let outputs = self.cardinality_network(&inputs, m);
+                self.add_clause(&[-outputs[k]]);
+            }
+        }
+    }
+
+    // ref: C. Sinz, Towards an Optimal CNF Encoding of Boolean Cardinality
+    // Constraints (2005). counters[i][j] holds if more than j of the first
+    // i + 1 literals are true.
+    fn sequential_counter(&mut self, literals: &[Literal], k: usize) {
+        let n = literals.len();
+        let counters = (0..n - 1)
+            .map(|_| (0..k).map(|_| self.new_literal()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        self.add_clause(&[-literals[0], counters[0][0]]);
+        for &counter in &counters[0][1..] {
+            self.add_clause(&[-counter]);
+        }
+        for i in 1..n - 1 {
+            let (x, prev, cur) = (literals[i], &counters[i - 1], &counters[i]);
+            self.add_clause(&[-x, cur[0]]);
+            self.add_clause(&[-prev[0], cur[0]]);
+            for j in 1..k {
+                self.add_clause(&[-x, -prev[j - 1], cur[j]]);
+                self.add_clause(&[-prev[j], cur[j]]);
+            }
+            self.add_clause(&[-x, -prev[k - 1]]);
+        }
+        self.add_clause(&[-literals[n - 1], -counters[n - 2][k - 1]]);
+    }
+
+    // ref: O. Bailleux and Y. Boufkhad, Efficient CNF Encoding of Boolean
+    // Cardinality Constraints (2003). Returns outputs counting the true
+    // literals in unary: outputs[i] holds if more than i of them are, where
+    // the last output stands for `limit` or more.
+    pub(crate) fn totalizer(&mut self, literals: &[Literal], limit: usize) -> Vec<Literal> {
+        if literals.len() == 1 {
+            return literals.to_vec();
+        }
+        let (left, right) = literals.split_at(literals.len() / 2);
+        let a = self.totalizer(left, limit);
+        let b = self.totalizer(right, limit);
+
+        let m = (a.len() + b.len()).min(limit);
+        let outputs = (0..m).map(|_| self.new_literal()).collect::<Vec<_>>();
+        for i in 0..=a.len() {
+            for j in 0..=b.len() {
+                if i + j == 0 {
+                    continue;
+                }
+                let mut clause = Vec::with_capacity(3);
+                if i > 0 {
+                    clause.push(-a[i - 1]);
+                }
+                if j > 0 {
+                    clause.push(-b[j - 1]);
+                }
+                clause.push(outputs[(i + j).min(m) - 1]);
+                self.add_clause(&clause);
+            }
+        }
+        outputs
+    }
+
+    // ref: R. Asín et al., Cardinality Networks: a theoretical and empirical
+    // study (2011). Sorts `inputs`, whose length is a multiple of the power of
+    // two `m`, keeping the first `m` outputs.
+    fn cardinality_network(&mut self, inputs: &[Literal], m: usize) -> Vec<Literal> {
+        if inputs.len() == m {
+            return self.sorter(inputs);
+        }
+        let a = self.cardinality_network(&inputs[..m], m);
+        let b = self.cardinality_network(&inputs[m..], m);
+        let mut outputs = self.simplified_merge(&a, &b);
+        outputs.truncate(m);
+        outputs
+    }
+
+    fn sorter(&mut self, inputs: &[Literal]) -> Vec<Literal> {
+        if inputs.len() == 1 {
+            return inputs.to_vec();
+        }
+        let (left, right) = inputs.split_at(inputs.len() / 2);
+        let a = self.sorter(left);
+        let b = self.sorter(right);
+        self.merge(&a, &b)
+    }
+
+    // merges two sorted sequences of the same power-of-two length
+    fn merge(&mut self, a: &[Literal], b: &[Literal]) -> Vec<Literal> {
+        let n = a.len();
+        if n == 1 {
+            let (max, min) = self.comparator(a[0], b[0]);
+            return vec![max, min];
+        }
+        let odd = self.merge(&odd(a), &odd(b));
+        let even = self.merge(&even(a), &even(b));
+
+        let mut outputs = vec![odd[0]];
+        for i in 0..n - 1 {
+            let (max, min) = self.comparator(odd[i + 1], even[i]);
+            outputs.push(max);
+            outputs.push(min);
+        }
+        outputs.push(even[n - 1]);
+        outputs
+    }
+
+    // like `merge`, keeping only the first n + 1 outputs
+    fn simplified_merge(&mut self, a: &[Literal], b: &[Literal]) -> Vec<Literal> {
+        let n = a.len();
+        if n == 1 {
+            let (max, min) = self.comparator(a[0], b[0]);
+            return vec![max, min];
+        }
+        let odd = self.simplified_merge(&odd(a), &odd(b));
+        let even = self.simplified_merge(&even(a), &even(b));
+
+        let mut outputs = vec![odd[0]];
+        for i in 0..n / 2 {
+            let (max, min) = self.comparator(odd[i + 1], even[i]);
+            outputs.push(max);
+            outputs.push(min);
+        }
+        outputs
+    }
+
+    // the upward half of a comparator: max = a | b, min = a & b
+    fn comparator(&mut self, a: Literal, b: Literal) -> (Literal, Literal) {
+        let max = self.new_literal();
+        let min = self.new_literal();
+        self.add_clause(&[-a, max]);
+        self.add_clause(&[-b, max]);
+        self.add_clause(&[-a, -b, min]);
+        (max, min)
+    }
+
+    fn false_literal(&mut self) -> Literal {
+        let literal = self.new_literal();
+        self.add_clause(&[-literal]);
+        literal
+    }
+
+    // ref: N. Eén and N. Sörensson, Translating Pseudo-Boolean Constraints
+    // into SAT (2006). Sums the terms bit by bit with full and half adders and
+    // compares the binary result to `bound`.
+    pub(crate) fn encode_pb(&mut self, terms: &[(i128, Literal)], bound: i128) {
+        let mut buckets = vec![Vec::new(); 128];
+        for &(a, literal) in terms {
+            for (bit, bucket) in buckets.iter_mut().enumerate() {
+                if a >> bit & 1 == 1 {
+                    bucket.push(literal);
+                }
+            }
+        }
+
+        // bits[i] is the i-th bit of the sum, None if always false
+        let mut bits = Vec::new();
+        for bit in 0..buckets.len() {
+            let mut i = 0;
+            while buckets[bit].len() - i >= 2 {
+                let carry = match buckets[bit].len() - i {
+                    2 => {
+                        let (x, y) = (buckets[bit][i], buckets[bit][i + 1]);
+                        i += 2;
+                        self.half_adder(x, y, &mut buckets[bit])
+                    }
+                    _ => {
+                        let (x, y, z) = (buckets[bit][i], buckets[bit][i + 1], buckets[bit][i + 2]);
+                        i += 3;
+                        self.full_adder(x, y, z, &mut buckets[bit])
+                    }
+                };
+                if bit + 1 < buckets.len() {
+                    buckets[bit + 1].push(carry);
+                }
+            }
+            bits.push(buckets[bit].get(i).cloned());
+        }
+
+        // the sum is below the bound if it agrees with it on the higher bits
+        // and has a 0 where the bound has a 1
+        for i in 0..bits.len() {
+            if bound >> i & 1 == 0 {
+                continue;
+            }
+            let mut clause = bits[i].into_iter().collect::<Vec<_>>();
+            let mut satisfied = false;
+            for (j, bit) in bits.iter().enumerate().skip(i + 1) {
+                match (bound >> j & 1 == 1, bit) {
+                    (true, Some(bit)) => clause.push(-*bit),
+                    (true, None) => satisfied = true,
+                    (false, Some(bit)) => clause.push(*bit),
+                    (false, None) => {}
+                }
+            }
+            if !satisfied {
+                self.add_clause(&clause);
+            }
+        }
+    }
+
+    // pushes the sum bit to `bucket` and returns the carry
+    fn full_adder(
+        &mut self,
+        x: Literal,
+        y: Literal,
+        z: Literal,
+        bucket: &mut Vec<Literal>,
+    ) -> Literal {
+        let sum = self.new_literal();
+        let carry = self.new_literal();
+        self.encode_parity(&[x, y, z, sum]);
+        for (a, b) in [(x, y), (x, z), (y, z)] {
+            self.add_clause(&[-a, -b, carry]);
+            self.add_clause(&[a, b, -carry]);
+        }
+        bucket.push(sum);
+        carry
+    }
+
+    fn half_adder(&mut self, x: Literal, y: Literal, bucket: &mut Vec<Literal>) -> Literal {
+        let sum = self.new_literal();
+        let carry = self.new_literal();
+        self.encode_parity(&[x, y, sum]);
+        self.add_clause(&[-x, -y, carry]);
+        self.add_clause(&[x, -carry]);
+        self.add_clause(&[y, -carry]);
+        bucket.push(sum);
+        carry
+    }
+
+    // an even number of `literals` holds
+    fn encode_parity(&mut self, literals: &[Literal]) {
+        let vars = literals.iter().map(|l| l.var).collect();
+        let rhs = literals.iter().fold(false, |rhs, l| rhs ^ l.inverted);
+        self.encode_xor(vars, rhs);
+    }
+}
+
+fn odd(literals: &[Literal]) -> Vec<Literal> {
+    literals.iter().step_by(2).cloned().collect()
+}
+
+fn even(literals: &[Literal]) -> Vec<Literal> {
+    literals.iter().skip(1).step_by(2).cloned().collect()
+}
@@ -0,0 +1,125 @@
+use super::{
+    result::SolveResult,
+    solver::Solver,
+    types::{Literal, Model, Variable},
+};
+
+/// The models of a formula projected onto some variables, as returned by
+/// [`Solver::enumerate_models`].
+pub struct Models<'a> {
+    solver: &'a mut Solver,
+    projection: Vec<Variable>,
+    // the assumptions, the last of which enables the blocking clauses
+    assumptions: Vec<Literal>,
+    // projected variables that were not frozen before
+    thawed: Vec<Variable>,
+    // the number of models still to be returned, if limited
+    remaining: Option<usize>,
+    result: Option<SolveResult>,
+}
+
+impl Solver {
+    /// Enumerates the models of the formula that differ on `projection`, each
+    /// restricted to those variables, stopping after `limit` models if given.
+    /// The `Limits` apply to the search for every single model.
+    ///
+    /// Each model is excluded by a blocking clause that only holds during the
+    /// enumeration, so the solver can be used as before once the iterator is
+    /// dropped.
+    pub fn enumerate_models(
+        &mut self,
+        projection: &[Variable],
+        limit: Option<usize>,
+    ) -> Models<'_> {
+        self.enumerate_models_with_assumptions(projection, &[], limit)
+    }
+
+    /// Enumerates the models satisfying `assumptions` like `enumerate_models`.
+    pub fn enumerate_models_with_assumptions(
+        &mut self,
+        projection: &[Variable],
+        assumptions: &[Literal],
+        limit: Option<usize>,
+    ) -> Models<'_> {
+        let mut projection = projection.to_vec();
+        projection.sort_unstable();
+        projection.dedup();
+
+        // the blocking clauses would bring eliminated variables back anyway
+        let mut thawed = Vec::new();
+        for &var in &projection {
+            self.reserve_variables(var);
+            if !self.frozen[var] {
+                self.freeze(var);
+                thawed.push(var);
+            }
+        }
+        let mut assumptions = assumptions.to_vec();
+        assumptions.push(self.new_literal());
+        Models {
+            solver: self,
+            projection,
+            assumptions,
+            thawed,
+            remaining: limit,
+            result: None,
+        }
+    }
+}
+
+impl Models<'_> {
+    /// How the enumeration ended: `Unsat` once every model was found, or
+    /// `Unknown` if a limit stopped it. Stays None after `limit` models.
+    pub fn result(&self) -> Option<SolveResult> {
+        self.result
+    }
+
+    fn active(&self) -> Literal {
+        *self.assumptions.last().unwrap()
+    }
+}
+
+impl Iterator for Models<'_> {
+    type Item = Model;
+
+    fn next(&mut self) -> Option<Model> {
+        if self.result.is_some() || self.remaining == Some(0) {
+            return None;
+        }
+        match self.solver.check_with_assumptions(&self.assumptions) {
+            SolveResult::Sat => {}
+            result => {
+                self.result = Some(result);
+                return None;
+            }
+        }
+
+        let model = self
+            .projection
+            .iter()
+            .map(|&var| (var, self.solver.model[var]))
+            .collect::<Model>();
+        let mut blocking = self
+            .projection
+            .iter()
+            .map(|&var| Literal::new(var, model[&var]))
+            .collect::<Vec<_>>();
+        blocking.push(-self.active());
+        // preprocessing before every model would cost more than the search,
+        // and the blocking clauses are temporary anyway
+        self.solver.add_clause_unscheduled(&blocking);
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(model)
+    }
+}
+
+impl Drop for Models<'_> {
+    fn drop(&mut self) {
+        self.solver.add_clause(&[-self.active()]);
+        for &var in &self.thawed {
+            self.solver.unfreeze(var);
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use super::types::Variable;
+
+/// Chooses the next decision variable. The polarity is decided by the solver
+/// (phase saving), so implementations only have to order variables.
+pub trait DecisionHeuristic {
+    /// Registers a variable. Variables are numbered from 1 and added in order.
+    fn new_variable(&mut self, var: Variable);
+
+    /// Called for every variable involved in a conflict.
+    fn bump(&mut self, var: Variable);
+
+    /// Called once after each conflict has been analyzed.
+    fn on_conflict(&mut self) {}
+
+    /// Called when a variable becomes unassigned during backtracking.
+    fn unassign(&mut self, var: Variable);
+
+    /// Called when a variable is eliminated from the formula. `next` must not
+    /// return it until it is restored.
+    fn remove(&mut self, var: Variable);
+
+    /// Called when an eliminated variable comes back.
+    fn restore(&mut self, var: Variable);
+
+    /// Returns an unassigned variable, or `None` if every variable is assigned.
+    fn next(&mut self, assigns: &[Option<bool>]) -> Option<Variable>;
+}
+
+/// Exponential VSIDS: activities are bumped by a growing increment and kept in
+/// a binary max-heap.
+pub struct Vsids {
+    activity: Vec<f64>,
+    increment: f64,
+    decay: f64,
+    heap: VarHeap,
+}
+
+impl Default for Vsids {
+    fn default() -> Self {
+        Self::new(0.95)
+    }
+}
+
+impl Vsids {
+    pub fn new(decay: f64) -> Self {
+        Self {
+            activity: vec![0.0],
+            increment: 1.0,
+            decay,
+            heap: VarHeap::new(),
+        }
+    }
+}
+
+impl DecisionHeuristic for Vsids {
+    fn new_variable(&mut self, var: Variable) {
+        self.activity.resize(var + 1, 0.0);
+        self.heap.insert(var, &self.activity);
+    }
+
+    fn bump(&mut self, var: Variable) {
+        self.activity[var] += self.increment;
+        if self.activity[var] > 1e100 {
+            for a in self.activity.iter_mut() {
+                *a *= 1e-100;
+            }
+            self.increment *= 1e-100;
+        }
+        self.heap.increased(var, &self.activity);
+    }
+
+    fn on_conflict(&mut self) {
+        self.increment /= self.decay;
+    }
+
+    fn unassign(&mut self, var: Variable) {
+        self.heap.insert(var, &self.activity);
+    }
+
+    fn remove(&mut self, var: Variable) {
+        self.heap.remove(var, &self.activity);
+    }
+
+    fn restore(&mut self, var: Variable) {
+        self.heap.insert(var, &self.activity);
+    }
+
+    fn next(&mut self, assigns: &[Option<bool>]) -> Option<Variable> {
+        while let Some(var) = self.heap.pop(&self.activity) {
+            if assigns[var].is_none() {
+                return Some(var);
+            }
+        }
+        None
+    }
+}
+
+/// Variable move-to-front: bumped variables are moved to the front of a queue
+/// and decisions are taken from the front.
+pub struct Vmtf {
+    prev: Vec<Variable>,
+    next: Vec<Variable>,
+    stamp: Vec<u64>,
+    counter: u64,
+    last: Variable,
+    search: Variable,
+}
+
+impl Default for Vmtf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vmtf {
+    pub fn new() -> Self {
+        Self {
+            prev: vec![0],
+            next: vec![0],
+            stamp: vec![0],
+            counter: 0,
+            last: 0,
+            search: 0,
+        }
+    }
+
+    fn dequeue(&mut self, var: Variable) {
+        let (prev, next) = (self.prev[var], self.next[var]);
+        if prev != 0 {
+            self.next[prev] = next;
+        }
+        match next {
+            0 => self.last = prev,
+            _ => self.prev[next] = prev,
+        }
+    }
+
+    // the most recently bumped variable is kept at `last`
+    fn enqueue(&mut self, var: Variable) {
+        self.prev[var] = self.last;
+        self.next[var] = 0;
+        if self.last != 0 {
+            self.next[self.last] = var;
+        }
+        self.last = var;
+        self.counter += 1;
+        self.stamp[var] = self.counter;
+    }
+}
+
+impl DecisionHeuristic for Vmtf {
+    fn new_variable(&mut self, var: Variable) {
+        self.prev.resize(var + 1, 0);
+        self.next.resize(var + 1, 0);
+        self.stamp.resize(var + 1, 0);
+        self.enqueue(var);
+        self.search = var;
+    }
+
+    fn bump(&mut self, var: Variable) {
+        if self.last == var {
+            return;
+        }
+        self.dequeue(var);
+        self.enqueue(var);
+    }
+
+    fn unassign(&mut self, var: Variable) {
+        if self.search == 0 || self.stamp[var] > self.stamp[self.search] {
+            self.search = var;
+        }
+    }
+
+    fn remove(&mut self, var: Variable) {
+        if self.search == var {
+            self.search = self.prev[var];
+        }
+        self.dequeue(var);
+    }
+
+    fn restore(&mut self, var: Variable) {
+        self.enqueue(var);
+        self.unassign(var);
+    }
+
+    fn next(&mut self, assigns: &[Option<bool>]) -> Option<Variable> {
+        // every variable enqueued after `search` is assigned
+        while self.search != 0 && assigns[self.search].is_some() {
+            self.search = self.prev[self.search];
+        }
+        match self.search {
+            0 => None,
+            var => Some(var),
+        }
+    }
+}
+
+/// Always branches on the unassigned variable with the lowest index. Useful
+/// for debugging since the search becomes fully deterministic.
+#[derive(Default)]
+pub struct FixedOrder {
+    num_vars: usize,
+    next: Variable,
+    removed: Vec<bool>,
+}
+
+impl FixedOrder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl DecisionHeuristic for FixedOrder {
+    fn new_variable(&mut self, var: Variable) {
+        self.num_vars = self.num_vars.max(var);
+        self.removed.resize(self.num_vars + 1, false);
+        if self.next == 0 {
+            self.next = var;
+        }
+    }
+
+    fn bump(&mut self, _var: Variable) {}
+
+    fn unassign(&mut self, var: Variable) {
+        if self.next == 0 || var < self.next {
+            self.next = var;
+        }
+    }
+
+    fn remove(&mut self, var: Variable) {
+        self.removed[var] = true;
+    }
+
+    fn restore(&mut self, var: Variable) {
+        self.removed[var] = false;
+        self.unassign(var);
+    }
+
+    fn next(&mut self, assigns: &[Option<bool>]) -> Option<Variable> {
+        if self.next == 0 {
+            return None;
+        }
+        while self.next <= self.num_vars
+            && (assigns[self.next].is_some() || self.removed[self.next])
+        {
+            self.next += 1;
+        }
+        match self.next <= self.num_vars {
+            true => Some(self.next),
+            false => None,
+        }
+    }
+}
+
+/// Binary max-heap of variables ordered by an external activity array.
+struct VarHeap {
+    heap: Vec<Variable>,
+    indices: Vec<Option<usize>>,
+}
+
+impl VarHeap {
+    fn new() -> Self {
+        Self {
+            heap: Vec::new(),
+            indices: vec![None],
+        }
+    }
+
+    fn insert(&mut self, var: Variable, activity: &[f64]) {
+        if var >= self.indices.len() {
+            self.indices.resize(var + 1, None);
+        }
+        if self.indices[var].is_some() {
+            return;
+        }
+
+        self.indices[var] = Some(self.heap.len());
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1, activity);
+    }
+
+    fn increased(&mut self, var: Variable, activity: &[f64]) {
+        if let Some(i) = self.indices[var] {
+            self.sift_up(i, activity);
+        }
+    }
+
+    fn pop(&mut self, activity: &[f64]) -> Option<Variable> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let top = self.heap.swap_remove(0);
+        self.indices[top] = None;
+        if !self.heap.is_empty() {
+            self.indices[self.heap[0]] = Some(0);
+            self.sift_down(0, activity);
+        }
+        Some(top)
+    }
+
+    fn remove(&mut self, var: Variable, activity: &[f64]) {
+        let Some(i) = self.indices.get(var).cloned().flatten() else {
+            return;
+        };
+        self.indices[var] = None;
+        let last = self.heap.pop().unwrap();
+        if i < self.heap.len() {
+            self.heap[i] = last;
+            self.indices[last] = Some(i);
+            self.sift_up(i, activity);
+            self.sift_down(self.indices[last].unwrap(), activity);
+        }
+    }
+
+    fn sift_up(&mut self, mut i: usize, activity: &[f64]) {
+        let var = self.heap[i];
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if activity[self.heap[parent]] >= activity[var] {
+                break;
+            }
+            self.heap[i] = self.heap[parent];
+            self.indices[self.heap[i]] = Some(i);
+            i = parent;
+        }
+        self.heap[i] = var;
+        self.indices[var] = Some(i);
+    }
+
+    fn sift_down(&mut self, mut i: usize, activity: &[f64]) {
+        let var = self.heap[i];
+        loop {
+            let left = 2 * i + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = match right < self.heap.len()
+                && activity[self.heap[right]] > activity[self.heap[left]]
+            {
+                true => right,
+                false => left,
+            };
+            if activity[self.heap[child]] <= activity[var] {
+                break;
+            }
+            self.heap[i] = self.heap[child];
+            self.indices[self.heap[i]] = Some(i);
+            i = child;
+        }
+        self.heap[i] = var;
+        self.indices[var] = Some(i);
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashSet;
+
+use super::{reduce::Tier, solver::Solver, types::Literal};
+
+#[derive(Debug, Clone)]
+pub struct InprocessConfig {
+    /// Runs with preprocessing and periodically during the search.
+    pub enabled: bool,
+    /// Probes literals for failed literals and hyper-binary resolvents.
+    pub probing: bool,
+    /// Substitutes literals equivalent by the binary implication graph.
+    pub equivalences: bool,
+    /// Number of conflicts between two rounds during the search.
+    pub interval: u64,
+    /// Propagations a round of probing may spend.
+    pub probe_budget: u64,
+}
+
+impl Default for InprocessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            probing: true,
+            equivalences: true,
+            interval: 5000,
+            probe_budget: 100_000,
+        }
+    }
+}
+
+impl Solver {
+    pub(crate) fn schedule_inprocess(&mut self) {
+        self.next_inprocess = self.stats.conflicts + self.config.inprocess.interval;
+    }
+
+    /// Simplifies the formula at decision level 0. Variables of `assumptions`
+    /// are not substituted.
+    pub(crate) fn inprocess(&mut self, assumptions: &[Literal]) {
+        debug_assert_eq!(self.decision_level(), 0);
+        if !self.config.inprocess.enabled {
+            return;
+        }
+        if self.propagate().is_some() {
+            self.log_add(&[]);
+            self.ok = false;
+            return;
+        }
+        self.simplify();
+
+        if self.config.inprocess.probing {
+            self.probe();
+        }
+        if self.ok && self.stopped.is_none() && self.config.inprocess.equivalences {
+            self.substitute_equivalences(assumptions);
+        }
+        if self.ok {
+            self.simplify();
+        }
+    }
+
+    // failed-literal probing: a literal whose propagation conflicts is fixed
+    // to false, and literals implied through a longer clause get a binary
+    // clause to their dominator in the implication graph
+    fn probe(&mut self) {
+        let phases = self.phases.clone();
+        let budget = self.stats.propagations + self.config.inprocess.probe_budget;
+        let num_literals = (self.num_vars + 1) * 2;
+        let mut implied = vec![false; num_literals];
+        let mut binaries = self.binary_clauses();
+        let mut tree = ImplicationTree::new(num_literals);
+
+        'vars: for var in 1..=self.num_vars {
+            for literal in [Literal::new(var, false), Literal::new(var, true)] {
+                if self.stats.propagations >= budget || self.should_stop() {
+                    break 'vars;
+                }
+                if self.value(literal).is_some() || self.eliminated[var] || implied[literal.index()]
+                {
+                    continue;
+                }
+
+                let start = self.trail.len();
+                self.trail_lim.push(start);
+                self.assign(literal, None);
+                if self.propagate().is_some() {
+                    self.cancel_until(0);
+                    self.stats.failed_literals += 1;
+                    self.log_add(&[-literal]);
+                    self.assign(-literal, None);
+                    if self.propagate().is_some() {
+                        self.log_add(&[]);
+                        self.ok = false;
+                        break 'vars;
+                    }
+                    continue;
+                }
+
+                let resolvents = self.hyper_binary_resolvents(start, &mut tree, &mut binaries);
+                for &l in &self.trail[start + 1..] {
+                    implied[l.index()] = true;
+                }
+                self.cancel_until(0);
+
+                for resolvent in resolvents {
+                    self.log_add(&resolvent);
+                    let cref = self.attach_clause(resolvent, Some(2));
+                    // unlike learned binaries these are many and cheap to find
+                    // again, so reductions may delete them
+                    self.clauses[cref].tier = Tier::Tier2;
+                    self.stats.hyper_binary += 1;
+                }
+            }
+        }
+
+        self.phases = phases;
+    }
+
+    // ref: M. Heule, M. Järvisalo and A. Biere, Revisiting Hyper Binary Resolution (2013)
+    // the new binary clauses from the dominators of the literals the probe implies
+    fn hyper_binary_resolvents(
+        &self,
+        start: usize,
+        tree: &mut ImplicationTree,
+        binaries: &mut HashSet<(Literal, Literal)>,
+    ) -> Vec<Vec<Literal>> {
+        let mut resolvents = Vec::new();
+        let root = self.trail[start];
+        tree.clear();
+        tree.insert(root, None);
+        for &l in &self.trail[start + 1..] {
+            let Some(cref) = self.reasons[l.var] else {
+                continue;
+            };
+            let literals = &self.clauses[cref].literals;
+            let mut antecedents = literals[1..]
+                .iter()
+                .map(|&a| -a)
+                .filter(|a| self.levels[a.var] > 0);
+            // implied by level 0 alone
+            let Some(first) = antecedents.next() else {
+                tree.insert(l, Some(root));
+                continue;
+            };
+            let dominator = antecedents.fold(first, |d, a| tree.common_dominator(d, a));
+            tree.insert(l, Some(dominator));
+
+            if literals.len() > 2 && binaries.insert(binary_key(-dominator, l)) {
+                resolvents.push(vec![-dominator, l]);
+            }
+        }
+        resolvents
+    }
+
+    // the binary clauses, each as the pair of its literals in index order
+    fn binary_clauses(&self) -> HashSet<(Literal, Literal)> {
+        self.clauses
+            .iter()
+            .filter(|clause| !clause.deleted && clause.literals.len() == 2)
+            .map(|clause| binary_key(clause.literals[0], clause.literals[1]))
+            .collect()
+    }
+
+    // finds the strongly connected components of the binary implication graph
+    // and replaces every literal by the representative of its component
+    fn substitute_equivalences(&mut self, assumptions: &[Literal]) {
+        let num_literals = (self.num_vars + 1) * 2;
+        let mut edges = vec![Vec::new(); num_literals];
+        for clause in &self.clauses {
+            if clause.deleted || clause.literals.len() != 2 {
+                continue;
+            }
+            let (a, b) = (clause.literals[0], clause.literals[1]);
+            if [a, b]
+                .iter()
+                .any(|l| self.value(*l).is_some() || self.eliminated[l.var])
+            {
+                continue;
+            }
+            edges[(-a).index()].push(b);
+            edges[(-b).index()].push(a);
+        }
+
+        let frozen = self.kept_variables(assumptions);
+
+        // substituted literals map to their representative
+        let mut repr = vec![None; num_literals];
+        let mut substituted = Vec::new();
+        let mut members = vec![false; num_literals];
+        for component in strongly_connected_components(&edges) {
+            if component.len() < 2 {
+                continue;
+            }
+            for l in &component {
+                members[l.index()] = true;
+            }
+            let contradiction = component.iter().find(|l| members[(-**l).index()]).cloned();
+            for l in &component {
+                members[l.index()] = false;
+            }
+            if let Some(l) = contradiction {
+                // l implies its own negation
+                self.log_add(&[-l]);
+                self.assign(-l, None);
+                self.log_add(&[]);
+                self.ok = false;
+                return;
+            }
+
+            let representative = *component
+                .iter()
+                .min_by_key(|l| (!frozen[l.var], l.var))
+                .unwrap();
+            for &l in &component {
+                // the mirrored component maps to the same variable again
+                if l != representative && !frozen[l.var] && repr[l.index()].is_none() {
+                    repr[l.index()] = Some(representative);
+                    repr[(-l).index()] = Some(-representative);
+                    substituted.push(l.var);
+                }
+            }
+        }
+        if substituted.is_empty() {
+            return;
+        }
+
+        // the equivalences are derived first and kept in the proof, so that
+        // restoring a variable needs no further steps
+        for &var in &substituted {
+            let literal = Literal::new(var, false);
+            let representative = repr[literal.index()].unwrap();
+            for clause in [
+                vec![literal, -representative],
+                vec![-literal, representative],
+            ] {
+                self.log_add(&clause);
+                self.elim_stack.push(clause);
+            }
+            self.eliminated[var] = true;
+            self.heuristic.remove(var);
+            self.stats.substituted += 1;
+        }
+
+        for cref in 0..self.clauses.len() {
+            let clause = &self.clauses[cref];
+            if clause.deleted || clause.literals.iter().all(|l| repr[l.index()].is_none()) {
+                continue;
+            }
+
+            let mut literals = Vec::with_capacity(clause.literals.len());
+            for &l in &clause.literals {
+                let l = repr[l.index()].unwrap_or(l);
+                if !literals.contains(&l) {
+                    literals.push(l);
+                }
+            }
+            if literals.iter().any(|&l| literals.contains(&-l)) {
+                self.remove_clause(cref);
+                continue;
+            }
+            self.log_add(&literals);
+            let old = std::mem::replace(&mut self.clauses[cref].literals, literals);
+            self.log_delete(&old);
+        }
+
+        self.rebuild_watches();
+    }
+}
+
+fn binary_key(a: Literal, b: Literal) -> (Literal, Literal) {
+    match a.index() <= b.index() {
+        true => (a, b),
+        false => (b, a),
+    }
+}
+
+// the implication tree of a probe, rooted at the probed literal
+struct ImplicationTree {
+    parents: Vec<Option<Literal>>,
+    depths: Vec<usize>,
+    members: Vec<Literal>,
+}
+
+impl ImplicationTree {
+    fn new(num_literals: usize) -> Self {
+        Self {
+            parents: vec![None; num_literals],
+            depths: vec![0; num_literals],
+            members: Vec::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        for l in self.members.drain(..) {
+            self.parents[l.index()] = None;
+        }
+    }
+
+    fn insert(&mut self, literal: Literal, parent: Option<Literal>) {
+        self.parents[literal.index()] = parent;
+        self.depths[literal.index()] = parent.map_or(0, |p| self.depths[p.index()] + 1);
+        self.members.push(literal);
+    }
+
+    // the deepest literal that every path from the root to `a` and `b` passes
+    fn common_dominator(&self, mut a: Literal, mut b: Literal) -> Literal {
+        while a != b {
+            let (depth_a, depth_b) = (self.depths[a.index()], self.depths[b.index()]);
+            if depth_a >= depth_b {
+                a = self.parents[a.index()].unwrap();
+            }
+            if depth_b >= depth_a {
+                b = self.parents[b.index()].unwrap();
+            }
+        }
+        a
+    }
+}
+
+fn literal_of(index: usize) -> Literal {
+    Literal::new(index / 2, index % 2 == 1)
+}
+
+// iterative Tarjan over literal indices, yielding the components as literals
+fn strongly_connected_components(edges: &[Vec<Literal>]) -> Vec<Vec<Literal>> {
+    const UNVISITED: usize = usize::MAX;
+    let n = edges.len();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut counter = 0;
+
+    for root in 0..n {
+        if index[root] != UNVISITED || edges[root].is_empty() {
+            continue;
+        }
+
+        // (node, position of the next edge to follow)
+        let mut calls = vec![(root, 0)];
+        index[root] = counter;
+        lowlink[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (node, ref mut next)) = calls.last_mut() {
+            if let Some(&target) = edges[node].get(*next) {
+                *next += 1;
+                let target = target.index();
+                if index[target] == UNVISITED {
+                    index[target] = counter;
+                    lowlink[target] = counter;
+                    counter += 1;
+                    stack.push(target);
+                    on_stack[target] = true;
+                    calls.push((target, 0));
+                } else if on_stack[target] {
+                    lowlink[node] = lowlink[node].min(index[target]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if lowlink[node] == index[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(literal_of(member));
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
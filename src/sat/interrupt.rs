@@ -0,0 +1,26 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Stops a running `check` call from another thread. The call returns
+/// `SolveResult::Unknown(Reason::Interrupted)`; an interrupt raised while no
+/// call is running stops the next one.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle {
+    flag: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.flag.store(false, Ordering::Relaxed);
+    }
+}
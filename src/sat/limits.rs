@@ -0,0 +1,10 @@
+use std::time::Instant;
+
+/// Bounds on a single `check` call. Conflicts and propagations are counted
+/// from the start of the call.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub conflicts: Option<u64>,
+    pub propagations: Option<u64>,
+    pub deadline: Option<Instant>,
+}
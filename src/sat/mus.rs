@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+
+use super::{result::SolveResult, solver::Solver, types::Literal};
+
+/// How [`Solver::minimal_unsat_subset`] shrinks a core to a minimal one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MusAlgorithm {
+    /// Drops the groups one by one, keeping those without which the formula
+    /// becomes satisfiable.
+    #[default]
+    Deletion,
+    /// Splits the groups in halves recursively, which takes fewer checks when
+    /// the subset is small compared to the core.
+    QuickXplain,
+}
+
+impl Solver {
+    /// Returns a new clause group, identified by the literal that enables its
+    /// clauses when assumed.
+    pub fn new_group(&mut self) -> Literal {
+        let selector = self.new_literal();
+        self.freeze(selector.var);
+        selector
+    }
+
+    /// Adds a clause that only holds while `group` is assumed.
+    pub fn add_group_clause(&mut self, group: Literal, literals: &[Literal]) {
+        let mut clause = literals.to_vec();
+        clause.push(-group);
+        self.add_clause(&clause);
+    }
+
+    /// Returns a minimal subset of `groups` that is unsatisfiable together
+    /// with the clauses outside of any group, in the order of `groups`, or
+    /// None unless all of them together are unsatisfiable.
+    ///
+    /// A check stopped by the `Limits` counts as satisfiable, so the result
+    /// is then still unsatisfiable but may not be minimal.
+    pub fn minimal_unsat_subset(
+        &mut self,
+        groups: &[Literal],
+        algorithm: MusAlgorithm,
+    ) -> Option<Vec<Literal>> {
+        if self.check_with_assumptions(groups) != SolveResult::Unsat {
+            return None;
+        }
+        let core = self.core_of(groups);
+        Some(match algorithm {
+            MusAlgorithm::Deletion => self.deletion_mus(core),
+            MusAlgorithm::QuickXplain => self.quick_xplain(&[], false, &core),
+        })
+    }
+
+    // the assumed groups that failed in the last check
+    fn core_of(&self, groups: &[Literal]) -> Vec<Literal> {
+        let failed = self
+            .failed_assumptions()
+            .into_iter()
+            .collect::<HashSet<_>>();
+        groups
+            .iter()
+            .filter(|group| failed.contains(group))
+            .cloned()
+            .collect()
+    }
+
+    // ref: J. Marques-Silva, Minimal Unsatisfiability: Models, Algorithms and
+    // Applications (2010). Every unsatisfiable check shrinks the core to its
+    // failed assumptions, which keeps the groups found necessary so far.
+    fn deletion_mus(&mut self, mut core: Vec<Literal>) -> Vec<Literal> {
+        let mut i = 0;
+        while i < core.len() {
+            let mut candidate = core.clone();
+            candidate.remove(i);
+            match self.check_with_assumptions(&candidate) {
+                SolveResult::Unsat => core = self.core_of(&candidate),
+                _ => i += 1,
+            }
+        }
+        core
+    }
+
+    // ref: U. Junker, QuickXplain: Preferred Explanations and Relaxations for
+    // Over-Constrained Problems (2004). Returns the groups of `candidates`
+    // that a minimal subset needs besides `background`, which only has to be
+    // checked when something was added to it.
+    fn quick_xplain(
+        &mut self,
+        background: &[Literal],
+        check_background: bool,
+        candidates: &[Literal],
+    ) -> Vec<Literal> {
+        if check_background && self.check_with_assumptions(background) == SolveResult::Unsat {
+            return Vec::new();
+        }
+        if candidates.len() <= 1 {
+            return candidates.to_vec();
+        }
+
+        let (left, right) = candidates.split_at(candidates.len() / 2);
+        let right = self.quick_xplain(&[background, left].concat(), true, right);
+        let left = self.quick_xplain(&[background, &right].concat(), !right.is_empty(), left);
+        [left, right].concat()
+    }
+}
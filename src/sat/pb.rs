@@ -0,0 +1,237 @@
+use super::{
+    solver::{ClauseRef, Solver},
+    types::{Literal, Variable},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    AtMost,
+    AtLeast,
+    Equal,
+}
+
+/// How cardinality and pseudo-Boolean constraints are added. The clausal
+/// encodings name the one used for cardinality constraints; constraints with
+/// different coefficients are encoded by adder networks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PbEncoding {
+    /// Propagate the constraints directly, next to the clauses.
+    #[default]
+    Native,
+    /// Sinz's sequential counter, O(n * k) clauses.
+    SequentialCounter,
+    /// A totalizer tree counting in unary up to k + 1.
+    Totalizer,
+    /// Cardinality networks of merge-sorting comparators, O(n log^2 k)
+    /// clauses.
+    CardinalityNetwork,
+}
+
+// sum of the terms >= bound, with coefficients in 1..=bound sorted from the
+// largest
+pub(crate) struct PbConstraint {
+    terms: Vec<(i128, Literal)>,
+    bound: i128,
+}
+
+impl Solver {
+    /// Adds the constraint that at most `k` of `literals` are true.
+    pub fn add_at_most(&mut self, literals: &[Literal], k: usize) {
+        let terms = literals.iter().map(|&l| (1, l)).collect::<Vec<_>>();
+        self.add_pb(&terms, Comparison::AtMost, k as i64);
+    }
+
+    /// Adds the constraint that at least `k` of `literals` are true.
+    pub fn add_at_least(&mut self, literals: &[Literal], k: usize) {
+        let terms = literals.iter().map(|&l| (1, l)).collect::<Vec<_>>();
+        self.add_pb(&terms, Comparison::AtLeast, k as i64);
+    }
+
+    /// Adds the constraint that the sum of the coefficients of the true
+    /// literals in `terms` compares to `rhs` by `cmp`.
+    ///
+    /// The constraint is propagated or encoded as the configured
+    /// `PbEncoding` says. Native propagation cannot be expressed in DRAT, so
+    /// while a proof is being written the constraint is encoded with a
+    /// totalizer or an adder network instead, which the proof takes as part
+    /// of the input.
+    pub fn add_pb(&mut self, terms: &[(i64, Literal)], cmp: Comparison, rhs: i64) {
+        if matches!(cmp, Comparison::AtLeast | Comparison::Equal) {
+            self.add_pb_at_least(terms.iter().map(|&(a, l)| (a as i128, l)), rhs as i128);
+        }
+        if matches!(cmp, Comparison::AtMost | Comparison::Equal) {
+            self.add_pb_at_least(
+                terms.iter().map(|&(a, l)| (-(a as i128), l)),
+                -(rhs as i128),
+            );
+        }
+    }
+
+    pub(crate) fn add_pb_at_least<I: Iterator<Item = (i128, Literal)>>(
+        &mut self,
+        terms: I,
+        bound: i128,
+    ) {
+        if !self.ok {
+            return;
+        }
+        self.cancel_until(0);
+        self.preprocess_pending = true;
+
+        // coefficients of the positive literals, fixed variables moved to the
+        // bound
+        let mut bound = bound;
+        let mut coefficients: Vec<(Variable, i128)> = Vec::new();
+        for (a, literal) in terms {
+            self.reserve_variables(literal.var);
+            if self.eliminated[literal.var] {
+                self.restore(literal.var);
+            }
+            // a * !x = a - a * x
+            let a = match literal.inverted {
+                true => {
+                    bound -= a;
+                    -a
+                }
+                false => a,
+            };
+            match self.assigns[literal.var] {
+                Some(value) => bound -= a * value as i128,
+                None => coefficients.push((literal.var, a)),
+            }
+        }
+        coefficients.sort_unstable_by_key(|&(var, _)| var);
+
+        // negative coefficients turn to positive ones of the negated literal
+        let mut terms = Vec::<(i128, Literal)>::new();
+        for (var, a) in coefficients {
+            match terms.last_mut() {
+                Some((b, l)) if l.var == var => *b += a,
+                _ => terms.push((a, Literal::new(var, false))),
+            }
+        }
+        let mut terms = terms
+            .into_iter()
+            .filter(|&(a, _)| a != 0)
+            .map(|(a, l)| match a < 0 {
+                true => {
+                    bound -= a;
+                    (-a, -l)
+                }
+                false => (a, l),
+            })
+            .collect::<Vec<_>>();
+
+        if bound <= 0 {
+            return;
+        }
+        for (a, _) in terms.iter_mut() {
+            *a = (*a).min(bound);
+        }
+        if terms.iter().map(|&(a, _)| a).sum::<i128>() < bound {
+            self.add_clause(&[]);
+            return;
+        }
+        terms.sort_by_key(|&(a, _)| std::cmp::Reverse(a));
+
+        let encoding = match self.config.pb {
+            PbEncoding::Native if self.proof.is_some() => PbEncoding::Totalizer,
+            encoding => encoding,
+        };
+        let literals = terms.iter().map(|&(_, l)| l).collect::<Vec<_>>();
+        let cardinality = terms.iter().all(|&(a, _)| a == terms[0].0);
+        if cardinality {
+            // at least `needed` of the literals
+            let needed = (bound + terms[0].0 - 1) / terms[0].0;
+            if needed == 1 {
+                self.add_clause(&literals);
+                return;
+            }
+            if needed == literals.len() as i128 {
+                for &literal in &literals {
+                    self.add_clause(&[literal]);
+                }
+                return;
+            }
+            if encoding != PbEncoding::Native {
+                let negated = literals.iter().map(|&l| -l).collect::<Vec<_>>();
+                self.encode_at_most(&negated, literals.len() - needed as usize, encoding);
+                return;
+            }
+            terms = literals.iter().map(|&l| (1, l)).collect();
+            bound = needed;
+        }
+        match encoding {
+            PbEncoding::Native => self.add_native_pb(PbConstraint { terms, bound }),
+            _ => self.encode_pb(&terms, bound),
+        }
+    }
+
+    fn add_native_pb(&mut self, constraint: PbConstraint) {
+        let index = self.pb_constraints.len();
+        for &(_, literal) in &constraint.terms {
+            self.native_vars[literal.var] = true;
+            self.pb_occurrences[literal.index()].push(index);
+        }
+        self.pb_constraints.push(constraint);
+
+        self.check_pb(index);
+        if self.ok && self.propagate().is_some() {
+            self.log_add(&[]);
+            self.ok = false;
+        }
+    }
+
+    pub(crate) fn propagate_pbs(&mut self) -> Option<ClauseRef> {
+        if self.pb_constraints.is_empty() {
+            return None;
+        }
+
+        let mut touched = Vec::new();
+        while self.pb_qhead < self.trail.len() {
+            let falsified = -self.trail[self.pb_qhead];
+            self.pb_qhead += 1;
+            touched.extend_from_slice(&self.pb_occurrences[falsified.index()]);
+        }
+        touched.sort_unstable();
+        touched.dedup();
+
+        for index in touched {
+            let conflict = self.check_pb(index);
+            if conflict.is_some() || !self.ok {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    // the literals whose coefficient exceeds the slack must hold, and a
+    // negative slack is a conflict; both are explained by the false literals
+    fn check_pb(&mut self, index: usize) -> Option<ClauseRef> {
+        let constraint = &self.pb_constraints[index];
+        let mut slack = -constraint.bound;
+        let mut falsified = Vec::new();
+        for &(a, literal) in &constraint.terms {
+            match self.value(literal) {
+                Some(false) => falsified.push(literal),
+                _ => slack += a,
+            }
+        }
+        if slack < 0 {
+            return self.explain_conflict(falsified);
+        }
+
+        let implied = constraint
+            .terms
+            .iter()
+            .take_while(|&&(a, _)| a > slack)
+            .filter(|&&(_, l)| self.value(l).is_none())
+            .map(|&(_, l)| l)
+            .collect::<Vec<_>>();
+        for literal in implied {
+            self.stats.pb_propagations += 1;
+            self.explain_implied(falsified.clone(), literal);
+        }
+        None
+    }
+}
@@ -0,0 +1,532 @@
+use super::{
+    reduce::Tier,
+    solver::{ClauseRef, Solver, StoredClause},
+    types::{Literal, Variable},
+};
+
+#[derive(Debug, Clone)]
+pub struct PreprocessConfig {
+    /// Runs before a `check` call whenever clauses were added since the last
+    /// run.
+    pub enabled: bool,
+    /// Removes subsumed clauses and strengthens clauses by self-subsuming
+    /// resolution.
+    pub subsumption: bool,
+    /// Eliminates variables whose resolvents do not outnumber their clauses.
+    pub elimination: bool,
+    /// Variables occurring in more clauses than this are not eliminated.
+    pub max_occurrences: usize,
+    /// Variables producing a longer resolvent are not eliminated.
+    pub max_resolvent_len: usize,
+    /// Number of clauses an elimination may add on top of the removed ones.
+    pub grow: usize,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            subsumption: true,
+            elimination: true,
+            max_occurrences: 32,
+            max_resolvent_len: 32,
+            grow: 0,
+        }
+    }
+}
+
+// occurrence lists of the irredundant clauses, only alive during a run.
+// Entries are removed lazily, so every use checks that the clause is still
+// alive and still contains the literal.
+struct Occurrences {
+    lists: Vec<Vec<ClauseRef>>,
+    queue: Vec<ClauseRef>,
+    queued: Vec<bool>,
+    units: Vec<Literal>,
+    touched: Vec<bool>,
+    frozen: Vec<bool>,
+    marks: Vec<bool>,
+}
+
+impl Solver {
+    /// Keeps `var` from being eliminated, restoring it if it already was.
+    /// Variables that are referred to after the first `check` call should be
+    /// frozen, otherwise they are brought back at some cost.
+    pub fn freeze(&mut self, var: Variable) {
+        self.reserve_variables(var);
+        self.frozen[var] = true;
+        if self.eliminated[var] {
+            self.restore(var);
+        }
+    }
+
+    pub fn unfreeze(&mut self, var: Variable) {
+        if var <= self.num_vars {
+            self.frozen[var] = false;
+        }
+    }
+
+    pub fn is_eliminated(&self, var: Variable) -> bool {
+        var <= self.num_vars && self.eliminated[var]
+    }
+
+    // variables the simplifications leave alone: the frozen ones, those of
+    // `assumptions` and those of constraints other than clauses
+    pub(crate) fn kept_variables(&self, assumptions: &[Literal]) -> Vec<bool> {
+        let mut kept = self.frozen.clone();
+        for literal in assumptions {
+            kept[literal.var] = true;
+        }
+        for (kept, &native) in kept.iter_mut().zip(&self.native_vars) {
+            *kept |= native;
+        }
+        kept
+    }
+
+    /// Simplifies the irredundant clauses at decision level 0. Variables of
+    /// `assumptions` are kept like frozen ones.
+    pub(crate) fn preprocess(&mut self, assumptions: &[Literal]) {
+        debug_assert_eq!(self.decision_level(), 0);
+        self.preprocess_pending = false;
+        if self.propagate().is_some() {
+            self.log_add(&[]);
+            self.ok = false;
+            return;
+        }
+        self.simplify();
+        self.inprocess(assumptions);
+        if !self.ok {
+            return;
+        }
+
+        let mut occs = Occurrences {
+            lists: vec![Vec::new(); (self.num_vars + 1) * 2],
+            queue: Vec::new(),
+            queued: Vec::new(),
+            units: Vec::new(),
+            touched: vec![true; self.num_vars + 1],
+            frozen: self.kept_variables(assumptions),
+            marks: vec![false; (self.num_vars + 1) * 2],
+        };
+
+        for cref in 0..self.clauses.len() {
+            let clause = &self.clauses[cref];
+            if clause.deleted || clause.learnt {
+                continue;
+            }
+            if clause.literals.iter().any(|&l| self.value(l) == Some(true)) {
+                self.remove_clause(cref);
+                continue;
+            }
+            if clause.literals.iter().any(|&l| self.value(l).is_some()) {
+                let literals = clause
+                    .literals
+                    .iter()
+                    .cloned()
+                    .filter(|&l| self.value(l).is_none())
+                    .collect();
+                self.replace_literals(&mut occs, cref, literals);
+            }
+            if !self.clauses[cref].deleted {
+                self.add_occurrences(&mut occs, cref);
+            }
+        }
+        self.propagate_units(&mut occs);
+
+        while self.ok && !self.should_stop() {
+            if self.config.preprocess.subsumption {
+                self.subsume_queued(&mut occs);
+            }
+            if !self.ok || !self.config.preprocess.elimination || self.eliminate(&mut occs) == 0 {
+                break;
+            }
+        }
+
+        self.rebuild_watches();
+    }
+
+    // brings back the clauses removed by eliminating `var`
+    pub(crate) fn restore(&mut self, var: Variable) {
+        self.eliminated[var] = false;
+        self.heuristic.restore(var);
+
+        let mut restored = Vec::new();
+        self.elim_stack.retain(|clause| {
+            if clause[0].var == var {
+                restored.push(clause.clone());
+                return false;
+            }
+            true
+        });
+
+        // the proof never saw the clauses go, see `try_eliminate`
+        for clause in restored {
+            self.add_clause(&clause);
+        }
+    }
+
+    // sets the eliminated variables in the model so that their clauses hold
+    pub(crate) fn extend_model(&mut self) {
+        for clause in self.elim_stack.iter().rev() {
+            let satisfied = clause.iter().any(|l| self.model[l.var] != l.inverted);
+            if !satisfied {
+                self.model[clause[0].var] = !clause[0].inverted;
+            }
+        }
+    }
+
+    fn add_occurrences(&mut self, occs: &mut Occurrences, cref: ClauseRef) {
+        for &literal in &self.clauses[cref].literals {
+            occs.lists[literal.index()].push(cref);
+            occs.touched[literal.var] = true;
+        }
+        self.enqueue(occs, cref);
+    }
+
+    fn enqueue(&mut self, occs: &mut Occurrences, cref: ClauseRef) {
+        if occs.queued.len() <= cref {
+            occs.queued.resize(cref + 1, false);
+        }
+        if !occs.queued[cref] {
+            occs.queued[cref] = true;
+            occs.queue.push(cref);
+        }
+    }
+
+    // clauses alive and still containing `literal`
+    fn occurrences(&self, occs: &mut Occurrences, literal: Literal) -> Vec<ClauseRef> {
+        let clauses = &self.clauses;
+        let list = &mut occs.lists[literal.index()];
+        list.retain(|&cref| !clauses[cref].deleted && clauses[cref].literals.contains(&literal));
+        list.clone()
+    }
+
+    // stores an irredundant clause without watching it
+    fn store_clause(&mut self, occs: &mut Occurrences, literals: Vec<Literal>) {
+        self.log_add(&literals);
+        if literals.len() == 1 {
+            occs.units.push(literals[0]);
+            return;
+        }
+
+        self.clauses.push(StoredClause {
+            literals,
+            learnt: false,
+            lbd: 0,
+            tier: Tier::Core,
+            used: false,
+            deleted: false,
+        });
+        self.add_occurrences(occs, self.clauses.len() - 1);
+    }
+
+    // replaces a clause by a subset of its literals
+    fn replace_literals(
+        &mut self,
+        occs: &mut Occurrences,
+        cref: ClauseRef,
+        literals: Vec<Literal>,
+    ) {
+        self.log_add(&literals);
+        let old = std::mem::replace(&mut self.clauses[cref].literals, literals);
+        self.log_delete(&old);
+
+        let clause = &mut self.clauses[cref];
+        match clause.literals.len() {
+            0 => self.ok = false,
+            1 => {
+                // the unit stays in the proof
+                occs.units.push(clause.literals[0]);
+                self.discard_clause(cref);
+            }
+            _ => {
+                for literal in &self.clauses[cref].literals {
+                    occs.touched[literal.var] = true;
+                }
+                self.enqueue(occs, cref);
+            }
+        }
+    }
+
+    // removes a clause without telling the proof
+    fn discard_clause(&mut self, cref: ClauseRef) {
+        let clause = &mut self.clauses[cref];
+        clause.literals.clear();
+        clause.deleted = true;
+        self.free_clauses.push(cref);
+    }
+
+    fn propagate_units(&mut self, occs: &mut Occurrences) {
+        while let Some(unit) = occs.units.pop() {
+            match self.value(unit) {
+                Some(true) => continue,
+                Some(false) => {
+                    self.log_add(&[]);
+                    self.ok = false;
+                    return;
+                }
+                None => self.assign(unit, None),
+            }
+
+            for cref in self.occurrences(occs, unit) {
+                self.remove_clause(cref);
+            }
+            for cref in self.occurrences(occs, -unit) {
+                self.strengthen(occs, cref, -unit);
+            }
+        }
+    }
+
+    fn strengthen(&mut self, occs: &mut Occurrences, cref: ClauseRef, literal: Literal) {
+        let literals = self.clauses[cref]
+            .literals
+            .iter()
+            .cloned()
+            .filter(|&l| l != literal)
+            .collect();
+        self.replace_literals(occs, cref, literals);
+    }
+
+    fn subsume_queued(&mut self, occs: &mut Occurrences) {
+        while let Some(cref) = occs.queue.pop() {
+            if self.should_stop() {
+                return;
+            }
+            occs.queued[cref] = false;
+            if !self.clauses[cref].deleted {
+                self.subsume(occs, cref);
+            }
+            self.propagate_units(occs);
+            if !self.ok {
+                return;
+            }
+        }
+    }
+
+    // backward subsumption and self-subsuming resolution with `cref`
+    fn subsume(&mut self, occs: &mut Occurrences, cref: ClauseRef) {
+        let literals = self.clauses[cref].literals.clone();
+        let pivot = *literals
+            .iter()
+            .min_by_key(|l| occs.lists[l.index()].len() + occs.lists[(-**l).index()].len())
+            .unwrap();
+
+        let mut candidates = self.occurrences(occs, pivot);
+        candidates.extend(self.occurrences(occs, -pivot));
+
+        for &literal in &literals {
+            occs.marks[literal.index()] = true;
+        }
+        for other in candidates {
+            let clause = &self.clauses[other];
+            if other == cref || clause.deleted || clause.literals.len() < literals.len() {
+                continue;
+            }
+
+            let mut same = 0;
+            let mut negated = None;
+            for &l in &clause.literals {
+                if occs.marks[l.index()] {
+                    same += 1;
+                } else if occs.marks[(-l).index()] {
+                    negated = Some(l);
+                }
+            }
+
+            if same == literals.len() {
+                self.remove_clause(other);
+                self.stats.subsumed += 1;
+            } else if same + 1 == literals.len() {
+                if let Some(l) = negated {
+                    self.strengthen(occs, other, l);
+                    self.stats.strengthened += 1;
+                }
+            }
+        }
+        for &literal in &literals {
+            occs.marks[literal.index()] = false;
+        }
+    }
+
+    // whether an irredundant clause is a subset of `literals`
+    fn is_subsumed(&mut self, occs: &mut Occurrences, literals: &[Literal]) -> bool {
+        for &literal in literals {
+            occs.marks[literal.index()] = true;
+        }
+        let mut subsumed = false;
+        for &literal in literals {
+            subsumed = self.occurrences(occs, literal).into_iter().any(|cref| {
+                let clause = &self.clauses[cref].literals;
+                clause.len() <= literals.len() && clause.iter().all(|l| occs.marks[l.index()])
+            });
+            if subsumed {
+                break;
+            }
+        }
+        for &literal in literals {
+            occs.marks[literal.index()] = false;
+        }
+        subsumed
+    }
+
+    // one round of bounded variable elimination over the touched variables,
+    // cheapest first. Returns the number of eliminated variables.
+    fn eliminate(&mut self, occs: &mut Occurrences) -> usize {
+        let mut candidates = (1..=self.num_vars)
+            .filter(|&var| occs.touched[var])
+            .map(|var| {
+                let pos = occs.lists[Literal::new(var, false).index()].len();
+                let neg = occs.lists[Literal::new(var, true).index()].len();
+                (pos * neg, var)
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        occs.touched.iter_mut().for_each(|t| *t = false);
+
+        let mut count = 0;
+        for (_, var) in candidates {
+            if self.should_stop() {
+                break;
+            }
+            if self.assigns[var].is_some() || self.eliminated[var] || occs.frozen[var] {
+                continue;
+            }
+            if self.try_eliminate(occs, var) {
+                count += 1;
+                if self.config.preprocess.subsumption {
+                    self.subsume_queued(occs);
+                }
+                self.propagate_units(occs);
+            }
+            if !self.ok {
+                break;
+            }
+        }
+        count
+    }
+
+    fn try_eliminate(&mut self, occs: &mut Occurrences, var: Variable) -> bool {
+        let config = &self.config.preprocess;
+        let positive = Literal::new(var, false);
+        let pos = self.occurrences(occs, positive);
+        let neg = self.occurrences(occs, -positive);
+        if pos.is_empty() && neg.is_empty() {
+            return false;
+        }
+        if pos.len() + neg.len() > config.max_occurrences && !pos.is_empty() && !neg.is_empty() {
+            return false;
+        }
+
+        let limit = pos.len() + neg.len() + config.grow;
+        let mut resolvents = Vec::new();
+        for &p in &pos {
+            for &n in &neg {
+                let resolvent = match self.resolve(p, n, var) {
+                    Some(resolvent) => resolvent,
+                    None => continue,
+                };
+                if resolvents.len() == limit || resolvent.len() > config.max_resolvent_len {
+                    return false;
+                }
+                resolvents.push(resolvent);
+            }
+        }
+
+        for resolvent in resolvents {
+            if !self.is_subsumed(occs, &resolvent) {
+                self.store_clause(occs, resolvent);
+            }
+        }
+        // the removed clauses stay in the proof: restoring them later would
+        // need RAT steps, which do not hold once clauses are added afterwards
+        for cref in pos.into_iter().chain(neg) {
+            let mut clause = self.clauses[cref].literals.clone();
+            let i = clause.iter().position(|l| l.var == var).unwrap();
+            clause.swap(0, i);
+            self.elim_stack.push(clause);
+            self.discard_clause(cref);
+        }
+        self.eliminated[var] = true;
+        self.heuristic.remove(var);
+        self.stats.eliminated += 1;
+        true
+    }
+
+    // resolvent on `var`, or `None` if it is a tautology
+    fn resolve(&self, a: ClauseRef, b: ClauseRef, var: Variable) -> Option<Vec<Literal>> {
+        let mut resolvent = self.clauses[a]
+            .literals
+            .iter()
+            .cloned()
+            .filter(|l| l.var != var)
+            .collect::<Vec<_>>();
+        for &literal in &self.clauses[b].literals {
+            if literal.var == var || resolvent.contains(&literal) {
+                continue;
+            }
+            if resolvent.contains(&-literal) {
+                return None;
+            }
+            resolvent.push(literal);
+        }
+        Some(resolvent)
+    }
+
+    // watches every remaining clause again, dropping learned clauses over
+    // eliminated variables and literals fixed in the meantime
+    pub(crate) fn rebuild_watches(&mut self) {
+        for watchers in self.watches.iter_mut() {
+            watchers.clear();
+        }
+        self.forget_reasons();
+        self.qhead = self.trail.len();
+
+        for cref in 0..self.clauses.len() {
+            let clause = &self.clauses[cref];
+            if clause.deleted {
+                continue;
+            }
+            if clause
+                .literals
+                .iter()
+                .any(|l| self.eliminated[l.var] || self.value(*l) == Some(true))
+            {
+                self.remove_clause(cref);
+                continue;
+            }
+
+            let literals = clause
+                .literals
+                .iter()
+                .cloned()
+                .filter(|&l| self.value(l).is_none())
+                .collect::<Vec<_>>();
+            if literals.len() < clause.literals.len() {
+                self.log_add(&literals);
+                let old = std::mem::replace(&mut self.clauses[cref].literals, literals);
+                self.log_delete(&old);
+            }
+
+            let clause = &mut self.clauses[cref];
+            match clause.literals.len() {
+                // the empty clause is in the proof already, and the other
+                // clauses are still watched for later calls
+                0 => {
+                    self.ok = false;
+                    self.discard_clause(cref);
+                }
+                1 => {
+                    let unit = clause.literals[0];
+                    self.discard_clause(cref);
+                    self.assign(unit, None);
+                }
+                _ => self.watch_clause(cref),
+            }
+        }
+
+        if self.ok && self.propagate().is_some() {
+            self.log_add(&[]);
+            self.ok = false;
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod checker;
+pub use checker::*;
+
+pub mod writer;
+pub use writer::*;
@@ -0,0 +1,773 @@
+use std::{collections::HashMap, fmt};
+
+use super::writer::ProofFormat;
+use crate::sat::types::{Cnf, Literal};
+
+// literals are handled as `Literal::index` codes: 2 * var + sign
+type Code = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofError {
+    /// The proof is malformed at the given line (text) or byte offset (binary).
+    Syntax { position: usize, message: String },
+    /// The lemma added by the given proof step is neither RUP nor RAT.
+    InvalidLemma { step: usize, clause: Vec<Literal> },
+    /// An LRAT step refers to a clause id which does not exist.
+    UnknownClause { step: usize, id: u64 },
+    /// The proof does not derive the empty clause.
+    NoConflict,
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::Syntax { position, message } => {
+                write!(f, "syntax error at {}: {}", position, message)
+            }
+            ProofError::InvalidLemma { step, clause } => {
+                let literals = clause
+                    .iter()
+                    .map(|l| format!("{}", l))
+                    .collect::<Vec<String>>();
+                write!(
+                    f,
+                    "step {}: lemma ({}) is not implied",
+                    step,
+                    literals.join(" || ")
+                )
+            }
+            ProofError::UnknownClause { step, id } => {
+                write!(f, "step {}: unknown clause id {}", step, id)
+            }
+            ProofError::NoConflict => write!(f, "the proof does not derive the empty clause"),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+/// Guesses whether a DRAT proof is in the text or the binary format.
+pub fn detect_format(proof: &[u8]) -> ProofFormat {
+    let start = proof.iter().position(|b| !b.is_ascii_whitespace());
+    let start = match start {
+        Some(start) => start,
+        None => return ProofFormat::Text,
+    };
+
+    let printable = |b: &u8| b.is_ascii_graphic() || b.is_ascii_whitespace();
+    let head = &proof[start..proof.len().min(start + 16)];
+    match head[0] {
+        b'a' => ProofFormat::Binary,
+        _ if !head.iter().all(printable) => ProofFormat::Binary,
+        b'd' if head.get(1) != Some(&b' ') => ProofFormat::Binary,
+        _ => ProofFormat::Text,
+    }
+}
+
+/// Checks a DRAT proof (text or binary) of the unsatisfiability of `cnf`.
+///
+/// Lemmas are verified backwards, starting from the conflict, and only those
+/// which take part in the refutation are checked. Propagation prefers clauses
+/// already known to be needed (core-first). Deletions follow the specified
+/// DRAT semantics, so deleted unit clauses are really deleted.
+pub fn check_drat(cnf: &Cnf, proof: &[u8]) -> Result<(), ProofError> {
+    check_drat_with_format(cnf, proof, detect_format(proof))
+}
+
+pub fn check_drat_with_format(
+    cnf: &Cnf,
+    proof: &[u8],
+    format: ProofFormat,
+) -> Result<(), ProofError> {
+    let steps = match format {
+        ProofFormat::Text => parse_text_drat(proof)?,
+        ProofFormat::Binary => parse_binary_drat(proof)?,
+    };
+    DratChecker::new(cnf).check(steps)
+}
+
+/// Checks a text LRAT proof of the unsatisfiability of `cnf`. The clauses of
+/// `cnf` have the ids 1, 2, ... in order.
+pub fn check_lrat(cnf: &Cnf, proof: &[u8]) -> Result<(), ProofError> {
+    LratChecker::new(cnf).check(proof)
+}
+
+enum DratStep {
+    Add(Vec<Code>),
+    Delete(Vec<Code>),
+}
+
+fn parse_code(value: i64) -> Code {
+    let var = value.unsigned_abs() as usize;
+    var * 2 + (value < 0) as usize
+}
+
+fn to_literal(code: Code) -> Literal {
+    Literal::new(code >> 1, code & 1 == 1)
+}
+
+fn parse_text_drat(proof: &[u8]) -> Result<Vec<DratStep>, ProofError> {
+    let text = String::from_utf8_lossy(proof);
+    let mut steps = Vec::new();
+    let mut literals = Vec::new();
+    let mut deletion = false;
+
+    for (i, line) in text.lines().enumerate() {
+        let mut tokens = line.split_ascii_whitespace().peekable();
+        if tokens.peek() == Some(&"c") {
+            continue;
+        }
+
+        for token in tokens {
+            if token == "d" && literals.is_empty() && !deletion {
+                deletion = true;
+                continue;
+            }
+
+            let value = token.parse::<i64>().map_err(|_| ProofError::Syntax {
+                position: i + 1,
+                message: format!("unexpected token `{}`", token),
+            })?;
+            if value != 0 {
+                literals.push(parse_code(value));
+                continue;
+            }
+
+            let clause = std::mem::take(&mut literals);
+            steps.push(match deletion {
+                true => DratStep::Delete(clause),
+                false => DratStep::Add(clause),
+            });
+            deletion = false;
+        }
+    }
+
+    if !literals.is_empty() || deletion {
+        return Err(ProofError::Syntax {
+            position: text.lines().count(),
+            message: "unterminated clause".into(),
+        });
+    }
+    Ok(steps)
+}
+
+fn parse_binary_drat(proof: &[u8]) -> Result<Vec<DratStep>, ProofError> {
+    let mut steps = Vec::new();
+    let mut pos = 0;
+
+    while pos < proof.len() {
+        let deletion = match proof[pos] {
+            b'a' => false,
+            b'd' => true,
+            b if b.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b => {
+                return Err(ProofError::Syntax {
+                    position: pos,
+                    message: format!("unexpected byte 0x{:02x}", b),
+                })
+            }
+        };
+        pos += 1;
+
+        let mut clause = Vec::new();
+        loop {
+            let mut code = 0;
+            let mut shift = 0;
+            loop {
+                let byte = *proof.get(pos).ok_or_else(|| ProofError::Syntax {
+                    position: pos,
+                    message: "unterminated clause".into(),
+                })?;
+                pos += 1;
+                code |= ((byte & 0x7f) as usize) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+
+            match code {
+                0 => break,
+                1 => {
+                    return Err(ProofError::Syntax {
+                        position: pos - 1,
+                        message: "invalid literal".into(),
+                    })
+                }
+                _ => clause.push(code),
+            }
+        }
+
+        steps.push(match deletion {
+            true => DratStep::Delete(clause),
+            false => DratStep::Add(clause),
+        });
+    }
+
+    Ok(steps)
+}
+
+#[derive(Clone, Copy)]
+enum Resolved {
+    Add(usize),
+    Delete(usize),
+}
+
+struct DratChecker {
+    clauses: Vec<Vec<Code>>,
+    // the first literal of each clause as written, as propagation reorders
+    // the clauses and RAT is checked on it
+    pivots: Vec<Option<Code>>,
+    active: Vec<bool>,
+    core: Vec<bool>,
+    watches: Vec<Vec<usize>>,
+    units: Vec<usize>,
+
+    assigns: Vec<Option<bool>>,
+    reasons: Vec<Option<usize>>,
+    seen: Vec<bool>,
+    trail: Vec<Code>,
+}
+
+impl DratChecker {
+    fn new(cnf: &Cnf) -> Self {
+        let mut checker = Self {
+            clauses: Vec::new(),
+            pivots: Vec::new(),
+            active: Vec::new(),
+            core: Vec::new(),
+            watches: Vec::new(),
+            units: Vec::new(),
+
+            assigns: Vec::new(),
+            reasons: Vec::new(),
+            seen: Vec::new(),
+            trail: Vec::new(),
+        };
+
+        for clause in &cnf.clauses {
+            let literals = clause.literals.iter().map(|l| l.index()).collect();
+            checker.add_clause(literals);
+        }
+        checker
+    }
+
+    fn check(mut self, steps: Vec<DratStep>) -> Result<(), ProofError> {
+        if self.clauses.iter().any(|c| c.is_empty()) {
+            return Ok(());
+        }
+
+        // forward pass: resolve deletions to clause ids until the empty clause
+        let mut lookup: HashMap<Vec<Code>, Vec<usize>> = HashMap::new();
+        for (id, clause) in self.clauses.iter().enumerate() {
+            lookup.entry(sorted(clause)).or_default().push(id);
+        }
+
+        let mut resolved = Vec::new();
+        for step in steps {
+            match step {
+                DratStep::Add(literals) => {
+                    let key = sorted(&literals);
+                    let empty = literals.is_empty();
+                    let id = self.add_clause(literals);
+                    lookup.entry(key).or_default().push(id);
+                    resolved.push(Resolved::Add(id));
+                    if empty {
+                        break;
+                    }
+                }
+                DratStep::Delete(literals) => {
+                    // deleting a clause which does not exist is not an error
+                    if let Some(id) = lookup.get_mut(&sorted(&literals)).and_then(|ids| ids.pop()) {
+                        self.active[id] = false;
+                        resolved.push(Resolved::Delete(id));
+                    }
+                }
+            }
+        }
+
+        if let Some(&Resolved::Add(id)) = resolved.last() {
+            if self.clauses[id].is_empty() {
+                self.active[id] = false;
+                resolved.pop();
+            }
+        }
+        if !self.implies(&[]) {
+            return Err(ProofError::NoConflict);
+        }
+
+        // backward pass: verify every lemma needed for the conflict
+        for (i, step) in resolved.into_iter().enumerate().rev() {
+            match step {
+                Resolved::Add(id) => {
+                    self.active[id] = false;
+                    if self.core[id] && !self.check_lemma(id) {
+                        return Err(ProofError::InvalidLemma {
+                            step: i + 1,
+                            clause: self.clauses[id].iter().map(|&c| to_literal(c)).collect(),
+                        });
+                    }
+                }
+                Resolved::Delete(id) => self.active[id] = true,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_clause(&mut self, literals: Vec<Code>) -> usize {
+        let id = self.clauses.len();
+        let mut unique = Vec::with_capacity(literals.len());
+        for code in literals {
+            self.reserve(code);
+            if !unique.contains(&code) {
+                unique.push(code);
+            }
+        }
+
+        let tautology = unique.iter().any(|&c| unique.contains(&(c ^ 1)));
+        match unique.len() {
+            0 => {}
+            1 => self.units.push(id),
+            _ if tautology => {}
+            _ => {
+                self.watches[unique[0]].push(id);
+                self.watches[unique[1]].push(id);
+            }
+        }
+
+        self.pivots.push(unique.first().cloned());
+        self.clauses.push(unique);
+        self.active.push(true);
+        self.core.push(false);
+        id
+    }
+
+    fn reserve(&mut self, code: Code) {
+        let var = code >> 1;
+        if var >= self.assigns.len() {
+            self.assigns.resize(var + 1, None);
+            self.reasons.resize(var + 1, None);
+            self.seen.resize(var + 1, false);
+            self.watches.resize((var + 1) * 2, Vec::new());
+        }
+    }
+
+    fn check_lemma(&mut self, id: usize) -> bool {
+        let lemma = self.clauses[id].clone();
+        if self.implies(&lemma) {
+            return true;
+        }
+
+        // RAT on the first literal
+        let pivot = match self.pivots[id] {
+            Some(pivot) => pivot,
+            None => return false,
+        };
+        let candidates = (0..self.clauses.len())
+            .filter(|&d| self.active[d] && self.clauses[d].contains(&(pivot ^ 1)))
+            .collect::<Vec<_>>();
+        for &d in &candidates {
+            let mut resolvent = lemma.clone();
+            resolvent.extend(self.clauses[d].iter().filter(|&&c| c != pivot ^ 1));
+            if !self.implies(&resolvent) {
+                return false;
+            }
+        }
+        for d in candidates {
+            self.core[d] = true;
+        }
+        true
+    }
+
+    // checks that unit propagation on the active clauses refutes the negation
+    // of `clause` and marks the clauses involved as core
+    fn implies(&mut self, clause: &[Code]) -> bool {
+        let conflict = self.refute(clause);
+        if let Some(Some(conflict)) = conflict {
+            self.mark_core(conflict);
+        }
+        self.reset();
+        conflict.is_some()
+    }
+
+    // Some(None) means that `clause` is a tautology
+    fn refute(&mut self, clause: &[Code]) -> Option<Option<usize>> {
+        for &code in clause {
+            match self.value(code) {
+                Some(true) => return Some(None),
+                Some(false) => {}
+                None => self.assign(code ^ 1, None),
+            }
+        }
+
+        for i in 0..self.units.len() {
+            let id = self.units[i];
+            if !self.active[id] {
+                continue;
+            }
+            let code = self.clauses[id][0];
+            match self.value(code) {
+                Some(true) => {}
+                Some(false) => return Some(Some(id)),
+                None => self.assign(code, Some(id)),
+            }
+        }
+
+        self.propagate().map(Some)
+    }
+
+    fn propagate(&mut self) -> Option<usize> {
+        let mut head_core = 0;
+        let mut head_all = 0;
+
+        loop {
+            let (code, core) = if head_core < self.trail.len() {
+                head_core += 1;
+                (self.trail[head_core - 1], true)
+            } else if head_all < self.trail.len() {
+                head_all += 1;
+                (self.trail[head_all - 1], false)
+            } else {
+                return None;
+            };
+
+            if let Some(conflict) = self.propagate_literal(code ^ 1, core) {
+                return Some(conflict);
+            }
+        }
+    }
+
+    fn propagate_literal(&mut self, false_code: Code, core: bool) -> Option<usize> {
+        let mut watchers = std::mem::take(&mut self.watches[false_code]);
+        let mut conflict = None;
+        let mut i = 0;
+        let mut j = 0;
+
+        while i < watchers.len() {
+            let id = watchers[i];
+            i += 1;
+            watchers[j] = id;
+            j += 1;
+            if !self.active[id] || self.core[id] != core {
+                continue;
+            }
+
+            let clause = &mut self.clauses[id];
+            if clause[0] == false_code {
+                clause.swap(0, 1);
+            }
+            let first = clause[0];
+            if code_value(&self.assigns, first) == Some(true) {
+                continue;
+            }
+
+            let assigns = &self.assigns;
+            let new_watch = clause[2..]
+                .iter()
+                .position(|&c| code_value(assigns, c) != Some(false));
+            if let Some(k) = new_watch.map(|k| k + 2) {
+                clause.swap(1, k);
+                self.watches[clause[1]].push(id);
+                j -= 1;
+                continue;
+            }
+
+            match code_value(&self.assigns, first) {
+                Some(false) => {
+                    conflict = Some(id);
+                    break;
+                }
+                _ => self.assign(first, Some(id)),
+            }
+        }
+
+        while i < watchers.len() {
+            watchers[j] = watchers[i];
+            i += 1;
+            j += 1;
+        }
+        watchers.truncate(j);
+        self.watches[false_code] = watchers;
+        conflict
+    }
+
+    fn mark_core(&mut self, conflict: usize) {
+        self.core[conflict] = true;
+        for &code in &self.clauses[conflict] {
+            self.seen[code >> 1] = true;
+        }
+
+        for i in (0..self.trail.len()).rev() {
+            let var = self.trail[i] >> 1;
+            if !self.seen[var] {
+                continue;
+            }
+            self.seen[var] = false;
+
+            if let Some(reason) = self.reasons[var] {
+                self.core[reason] = true;
+                for &code in &self.clauses[reason] {
+                    if code >> 1 != var {
+                        self.seen[code >> 1] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn value(&self, code: Code) -> Option<bool> {
+        code_value(&self.assigns, code)
+    }
+
+    fn assign(&mut self, code: Code, reason: Option<usize>) {
+        self.assigns[code >> 1] = Some(code & 1 == 0);
+        self.reasons[code >> 1] = reason;
+        self.trail.push(code);
+    }
+
+    fn reset(&mut self) {
+        for code in self.trail.drain(..) {
+            self.assigns[code >> 1] = None;
+            self.reasons[code >> 1] = None;
+            self.seen[code >> 1] = false;
+        }
+    }
+}
+
+struct LratChecker {
+    clauses: HashMap<u64, Vec<Code>>,
+    assigns: Vec<Option<bool>>,
+    trail: Vec<Code>,
+}
+
+enum Hint {
+    Conflict,
+    Unit(Code),
+    Invalid,
+}
+
+impl LratChecker {
+    fn new(cnf: &Cnf) -> Self {
+        let clauses = cnf
+            .clauses
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i as u64 + 1, c.literals.iter().map(|l| l.index()).collect()))
+            .collect();
+
+        Self {
+            clauses,
+            assigns: Vec::new(),
+            trail: Vec::new(),
+        }
+    }
+
+    fn check(mut self, proof: &[u8]) -> Result<(), ProofError> {
+        if self.clauses.values().any(|c| c.is_empty()) {
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(proof);
+        for (i, line) in text.lines().enumerate() {
+            let tokens = line.split_ascii_whitespace().collect::<Vec<_>>();
+            if tokens.is_empty() || tokens[0] == "c" {
+                continue;
+            }
+
+            let step = i + 1;
+            let numbers = |tokens: &[&str]| {
+                tokens
+                    .iter()
+                    .map(|t| t.parse::<i64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| ProofError::Syntax {
+                        position: step,
+                        message: "expected a number".into(),
+                    })
+            };
+
+            let id = numbers(&tokens[..1])?[0] as u64;
+            if tokens.get(1) == Some(&"d") {
+                for id in numbers(&tokens[2..])? {
+                    self.clauses.remove(&(id as u64));
+                }
+                continue;
+            }
+
+            let values = numbers(&tokens[1..])?;
+            let split = values.iter().position(|&v| v == 0);
+            let (literals, hints) = match split {
+                Some(split) if values.last() == Some(&0) && split + 1 < values.len() => {
+                    (&values[..split], &values[(split + 1)..(values.len() - 1)])
+                }
+                _ => {
+                    return Err(ProofError::Syntax {
+                        position: step,
+                        message: "expected `<id> <literals> 0 <hints> 0`".into(),
+                    })
+                }
+            };
+
+            let lemma = literals.iter().map(|&v| parse_code(v)).collect::<Vec<_>>();
+            let valid = self.check_lemma(step, &lemma, hints);
+            self.reset(0);
+            if !valid? {
+                return Err(ProofError::InvalidLemma {
+                    step,
+                    clause: lemma.into_iter().map(to_literal).collect(),
+                });
+            }
+            if lemma.is_empty() {
+                return Ok(());
+            }
+            self.clauses.insert(id, lemma);
+        }
+
+        Err(ProofError::NoConflict)
+    }
+
+    fn check_lemma(
+        &mut self,
+        step: usize,
+        lemma: &[Code],
+        hints: &[i64],
+    ) -> Result<bool, ProofError> {
+        for &code in lemma {
+            self.reserve(code);
+            match self.value(code) {
+                Some(true) => return Ok(true),
+                Some(false) => {}
+                None => self.assign(code ^ 1),
+            }
+        }
+
+        // RUP part: positive hints up to the first negative one
+        let rat_start = hints.iter().position(|&h| h < 0).unwrap_or(hints.len());
+        if self.follow_hints(step, &hints[..rat_start])? {
+            return Ok(true);
+        }
+
+        // RAT part: `-id hints...` for every clause containing the negated pivot
+        let pivot = match lemma.first() {
+            Some(&pivot) => pivot,
+            None => return Ok(false),
+        };
+        let mut groups = HashMap::new();
+        let mut rest = &hints[rat_start..];
+        while let Some((&head, tail)) = rest.split_first() {
+            let end = tail.iter().position(|&h| h < 0).unwrap_or(tail.len());
+            groups.insert((-head) as u64, &tail[..end]);
+            rest = &tail[end..];
+        }
+
+        let mut candidates = self
+            .clauses
+            .iter()
+            .filter(|(_, c)| c.contains(&(pivot ^ 1)))
+            .map(|(&id, c)| (id, c.clone()))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(id, _)| *id);
+
+        let base = self.trail.len();
+        for (id, clause) in candidates {
+            let mut blocked = false;
+            for &code in clause.iter().filter(|&&c| c != pivot ^ 1) {
+                self.reserve(code);
+                match self.value(code) {
+                    Some(true) => blocked = true,
+                    Some(false) => {}
+                    None => self.assign(code ^ 1),
+                }
+            }
+
+            let refuted = blocked
+                || match groups.get(&id) {
+                    Some(hints) => self.follow_hints(step, hints)?,
+                    None => false,
+                };
+            self.reset(base);
+            if !refuted {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    // returns true once a hint is falsified
+    fn follow_hints(&mut self, step: usize, hints: &[i64]) -> Result<bool, ProofError> {
+        for &hint in hints {
+            let id = hint as u64;
+            let clause = self
+                .clauses
+                .get(&id)
+                .ok_or(ProofError::UnknownClause { step, id })?;
+
+            let mut result = Hint::Conflict;
+            for &code in clause {
+                match code_value(&self.assigns, code) {
+                    Some(false) => {}
+                    Some(true) => result = Hint::Invalid,
+                    None => {
+                        result = match result {
+                            Hint::Conflict => Hint::Unit(code),
+                            _ => Hint::Invalid,
+                        }
+                    }
+                }
+                if let Hint::Invalid = result {
+                    break;
+                }
+            }
+
+            match result {
+                Hint::Conflict => return Ok(true),
+                Hint::Unit(code) => {
+                    self.reserve(code);
+                    self.assign(code);
+                }
+                Hint::Invalid => return Ok(false),
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn reserve(&mut self, code: Code) {
+        if code >> 1 >= self.assigns.len() {
+            self.assigns.resize((code >> 1) + 1, None);
+        }
+    }
+
+    fn value(&self, code: Code) -> Option<bool> {
+        code_value(&self.assigns, code)
+    }
+
+    fn assign(&mut self, code: Code) {
+        self.assigns[code >> 1] = Some(code & 1 == 0);
+        self.trail.push(code);
+    }
+
+    fn reset(&mut self, len: usize) {
+        for code in self.trail.drain(len..) {
+            self.assigns[code >> 1] = None;
+        }
+    }
+}
+
+fn code_value(assigns: &[Option<bool>], code: Code) -> Option<bool> {
+    assigns
+        .get(code >> 1)
+        .and_then(|v| v.map(|v| v != (code & 1 == 1)))
+}
+
+fn sorted(literals: &[Code]) -> Vec<Code> {
+    let mut key = literals.to_vec();
+    key.sort_unstable();
+    key.dedup();
+    key
+}
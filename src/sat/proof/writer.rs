@@ -0,0 +1,75 @@
+use std::io::{self, BufWriter, Write};
+
+use crate::sat::types::Literal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofFormat {
+    Text,
+    Binary,
+}
+
+/// Receives every clause the solver adds to or deletes from its database.
+pub trait ProofSink {
+    fn add(&mut self, literals: &[Literal]) -> io::Result<()>;
+    fn delete(&mut self, literals: &[Literal]) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Writes a DRAT proof in the text or binary format of drat-trim.
+pub struct DratWriter<W: Write> {
+    writer: BufWriter<W>,
+    format: ProofFormat,
+}
+
+impl<W: Write> DratWriter<W> {
+    pub fn new(writer: W, format: ProofFormat) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            format,
+        }
+    }
+
+    fn write_clause(&mut self, tag: u8, literals: &[Literal]) -> io::Result<()> {
+        match self.format {
+            ProofFormat::Text => {
+                if tag == b'd' {
+                    self.writer.write_all(b"d ")?;
+                }
+                for literal in literals {
+                    if literal.inverted {
+                        self.writer.write_all(b"-")?;
+                    }
+                    write!(self.writer, "{} ", literal.var)?;
+                }
+                self.writer.write_all(b"0\n")
+            }
+            ProofFormat::Binary => {
+                self.writer.write_all(&[tag])?;
+                for literal in literals {
+                    // 2 * var + sign as a 7-bit variable-length integer
+                    let mut code = literal.index();
+                    while code > 0x7f {
+                        self.writer.write_all(&[(code & 0x7f) as u8 | 0x80])?;
+                        code >>= 7;
+                    }
+                    self.writer.write_all(&[code as u8])?;
+                }
+                self.writer.write_all(&[0])
+            }
+        }
+    }
+}
+
+impl<W: Write> ProofSink for DratWriter<W> {
+    fn add(&mut self, literals: &[Literal]) -> io::Result<()> {
+        self.write_clause(b'a', literals)
+    }
+
+    fn delete(&mut self, literals: &[Literal]) -> io::Result<()> {
+        self.write_clause(b'd', literals)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,160 @@
+use super::solver::{ClauseRef, Solver};
+
+#[derive(Debug, Clone)]
+pub struct ReduceConfig {
+    /// Number of conflicts before the first reduction.
+    pub first: u64,
+    /// The interval between reductions grows by this many conflicts.
+    pub increment: u64,
+    /// Learned clauses with an LBD up to this value are kept forever.
+    pub core_lbd: usize,
+    /// Learned clauses with an LBD up to this value are kept while they keep
+    /// taking part in conflicts.
+    pub tier2_lbd: usize,
+    /// Fraction of the unused local clauses removed by a reduction.
+    pub fraction: f64,
+}
+
+impl Default for ReduceConfig {
+    fn default() -> Self {
+        Self {
+            first: 2000,
+            increment: 300,
+            core_lbd: 2,
+            tier2_lbd: 6,
+            fraction: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Tier {
+    Core,
+    Tier2,
+    Local,
+}
+
+impl Solver {
+    pub(crate) fn tier_of(&self, lbd: usize) -> Tier {
+        let config = &self.config.reduce;
+        if lbd <= config.core_lbd {
+            Tier::Core
+        } else if lbd <= config.tier2_lbd {
+            Tier::Tier2
+        } else {
+            Tier::Local
+        }
+    }
+
+    pub(crate) fn schedule_reduce(&mut self) {
+        let config = &self.config.reduce;
+        self.next_reduce += config.first + config.increment * self.reductions;
+    }
+
+    /// Marks a learned clause taking part in conflict analysis as used and
+    /// promotes it if its LBD improved.
+    pub(crate) fn bump_clause(&mut self, cref: ClauseRef) {
+        let lbd = self.compute_lbd_of(cref);
+        let tier = self.tier_of(lbd);
+
+        let clause = &mut self.clauses[cref];
+        clause.used = true;
+        if lbd < clause.lbd {
+            clause.lbd = lbd;
+            clause.tier = clause.tier.min(tier);
+        }
+    }
+
+    /// Removes learned clauses of low value: core clauses stay, tier2 clauses
+    /// unused since the last reduction are demoted and a fraction of the local
+    /// clauses with the highest LBD is deleted.
+    pub(crate) fn reduce_db(&mut self) {
+        self.reductions += 1;
+
+        let mut candidates = Vec::new();
+        for cref in 0..self.clauses.len() {
+            let clause = &mut self.clauses[cref];
+            if clause.deleted || !clause.learnt {
+                continue;
+            }
+
+            let used = std::mem::replace(&mut clause.used, false);
+            match clause.tier {
+                Tier::Core => {}
+                Tier::Tier2 => {
+                    if !used {
+                        clause.tier = Tier::Local;
+                    }
+                }
+                Tier::Local => {
+                    if !used && !self.is_locked(cref) {
+                        candidates.push(cref);
+                    }
+                }
+            }
+        }
+
+        candidates.sort_by_key(|&cref| {
+            let clause = &self.clauses[cref];
+            std::cmp::Reverse((clause.lbd, clause.literals.len()))
+        });
+        let num_removed = (candidates.len() as f64 * self.config.reduce.fraction) as usize;
+        for &cref in &candidates[..num_removed] {
+            self.remove_clause(cref);
+        }
+        self.stats.reduced += num_removed as u64;
+
+        self.clean_watches();
+    }
+
+    /// Removes every clause satisfied at decision level 0.
+    pub(crate) fn simplify(&mut self) {
+        debug_assert_eq!(self.decision_level(), 0);
+        if self.trail.len() == self.simplified_trail {
+            return;
+        }
+        self.simplified_trail = self.trail.len();
+        self.forget_reasons();
+
+        for cref in 0..self.clauses.len() {
+            let clause = &self.clauses[cref];
+            if !clause.deleted && clause.literals.iter().any(|&l| self.value(l) == Some(true)) {
+                self.remove_clause(cref);
+            }
+        }
+
+        self.clean_watches();
+    }
+
+    /// Level 0 assignments are never explained, so their reasons can go once
+    /// the proof knows the implied units.
+    pub(crate) fn forget_reasons(&mut self) {
+        debug_assert_eq!(self.decision_level(), 0);
+        for i in 0..self.trail.len() {
+            let literal = self.trail[i];
+            if self.reasons[literal.var].take().is_some() {
+                self.log_add(&[literal]);
+            }
+        }
+    }
+
+    pub(crate) fn is_locked(&self, cref: ClauseRef) -> bool {
+        let first = self.clauses[cref].literals[0];
+        self.reasons[first.var] == Some(cref) && self.value(first) == Some(true)
+    }
+
+    pub(crate) fn remove_clause(&mut self, cref: ClauseRef) {
+        let literals = std::mem::take(&mut self.clauses[cref].literals);
+        self.log_delete(&literals);
+        self.clauses[cref].deleted = true;
+        self.free_clauses.push(cref);
+        self.stats.deleted += 1;
+    }
+
+    fn clean_watches(&mut self) {
+        let clauses = &self.clauses;
+        for watchers in self.watches.iter_mut() {
+            watchers.retain(|w| !clauses[w.cref].deleted);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartStrategy {
+    /// Never restart.
+    None,
+    /// Restart after `first` conflicts, then multiply the interval by `factor`.
+    Geometric { first: u64, factor: f64 },
+    /// Restart intervals follow the Luby sequence scaled by `unit` conflicts.
+    Luby { unit: u64 },
+    /// Glucose-style: restart when the average LBD of the last `window`
+    /// learned clauses multiplied by `margin` exceeds the global average.
+    Glucose { window: usize, margin: f64 },
+}
+
+impl Default for RestartStrategy {
+    fn default() -> Self {
+        RestartStrategy::Glucose {
+            window: 50,
+            margin: 0.8,
+        }
+    }
+}
+
+/// Keeps track of when the next restart is due.
+pub(crate) struct RestartPolicy {
+    strategy: RestartStrategy,
+    conflicts: u64,
+    limit: f64,
+    restarts: u32,
+
+    recent_lbds: VecDeque<usize>,
+    recent_sum: usize,
+    total_lbds: u64,
+    total_sum: u64,
+}
+
+impl RestartPolicy {
+    pub(crate) fn new(strategy: RestartStrategy) -> Self {
+        let mut policy = Self {
+            strategy,
+            conflicts: 0,
+            limit: 0.0,
+            restarts: 0,
+
+            recent_lbds: VecDeque::new(),
+            recent_sum: 0,
+            total_lbds: 0,
+            total_sum: 0,
+        };
+        policy.limit = policy.next_limit();
+        policy
+    }
+
+    pub(crate) fn on_conflict(&mut self, lbd: usize) {
+        self.conflicts += 1;
+
+        if let RestartStrategy::Glucose { window, .. } = self.strategy {
+            self.recent_lbds.push_back(lbd);
+            self.recent_sum += lbd;
+            if self.recent_lbds.len() > window {
+                self.recent_sum -= self.recent_lbds.pop_front().unwrap();
+            }
+            self.total_lbds += 1;
+            self.total_sum += lbd as u64;
+        }
+    }
+
+    pub(crate) fn should_restart(&self) -> bool {
+        match self.strategy {
+            RestartStrategy::None => false,
+            RestartStrategy::Geometric { .. } | RestartStrategy::Luby { .. } => {
+                self.conflicts as f64 >= self.limit
+            }
+            RestartStrategy::Glucose { window, margin } => {
+                if self.recent_lbds.len() < window {
+                    return false;
+                }
+                let recent = self.recent_sum as f64 / window as f64;
+                let total = self.total_sum as f64 / self.total_lbds as f64;
+                recent * margin > total
+            }
+        }
+    }
+
+    pub(crate) fn on_restart(&mut self) {
+        self.conflicts = 0;
+        self.restarts += 1;
+        self.limit = self.next_limit();
+        self.recent_lbds.clear();
+        self.recent_sum = 0;
+    }
+
+    fn next_limit(&self) -> f64 {
+        match self.strategy {
+            RestartStrategy::Geometric { first, factor } => {
+                first as f64 * factor.powi(self.restarts as i32)
+            }
+            RestartStrategy::Luby { unit } => unit as f64 * luby(self.restarts as u64),
+            _ => 0.0,
+        }
+    }
+}
+
+// 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, ...
+fn luby(mut i: u64) -> f64 {
+    let mut size = 1;
+    let mut seq = 0;
+    while size < i + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+
+    while size - 1 != i {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        i %= size;
+    }
+
+    2f64.powi(seq)
+}
@@ -0,0 +1,15 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveResult {
+    Sat,
+    Unsat,
+    /// The search stopped before an answer was found.
+    Unknown(Reason),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    ConflictLimit,
+    PropagationLimit,
+    Timeout,
+    Interrupted,
+}
@@ -1,104 +1,880 @@
-use super::types::{Cnf, Literal, Model};
+use std::{
+    cmp::Reverse,
+    io::{self, Write},
+    time::Instant,
+};
+
+use super::{
+    config::SolverConfig,
+    heuristic::{DecisionHeuristic, Vsids},
+    interrupt::InterruptHandle,
+    limits::Limits,
+    pb::PbConstraint,
+    proof::{DratWriter, ProofFormat, ProofSink},
+    reduce::Tier,
+    restart::RestartPolicy,
+    result::{Reason, SolveResult},
+    stats::Statistics,
+    types::{Cnf, Literal, Model, Variable},
+    xor::{XorConstraint, XorMatrix},
+};
+
+pub(crate) type ClauseRef = usize;
+pub(crate) type ProgressCallback = Box<dyn FnMut(&Statistics)>;
+
+#[derive(Clone, Copy)]
+pub(crate) struct Watcher {
+    pub(crate) cref: ClauseRef,
+    pub(crate) blocker: Literal,
+}
+
+pub(crate) struct StoredClause {
+    pub(crate) literals: Vec<Literal>,
+    pub(crate) learnt: bool,
+    pub(crate) lbd: usize,
+    pub(crate) tier: Tier,
+    pub(crate) used: bool,
+    pub(crate) deleted: bool,
+}
 
 pub struct Solver {
-    formula: Cnf,
-    models: Vec<Model>,
-    literal_index: usize,
+    pub(crate) config: SolverConfig,
+    pub(crate) restart: RestartPolicy,
+    pub(crate) stats: Statistics,
+    pub(crate) timer: Option<Instant>,
+    pub(crate) progress: Option<(u64, ProgressCallback)>,
+    pub(crate) limits: Limits,
+    pub(crate) interrupt: InterruptHandle,
+    // the conflicts and propagations at the start of the running check, and
+    // the limit that stopped its preprocessing or inprocessing
+    pub(crate) start: (u64, u64),
+    pub(crate) stopped: Option<Reason>,
+
+    pub(crate) clauses: Vec<StoredClause>,
+    pub(crate) free_clauses: Vec<ClauseRef>,
+    pub(crate) watches: Vec<Vec<Watcher>>,
+    pub(crate) reductions: u64,
+    pub(crate) next_reduce: u64,
+    pub(crate) next_inprocess: u64,
+    pub(crate) simplified_trail: usize,
+    pub(crate) num_vars: usize,
+    pub(crate) ok: bool,
+
+    pub(crate) frozen: Vec<bool>,
+    pub(crate) eliminated: Vec<bool>,
+    // clauses removed by variable elimination, the eliminated literal first
+    pub(crate) elim_stack: Vec<Vec<Literal>>,
+    pub(crate) preprocess_pending: bool,
+
+    // variables of constraints propagated without clauses
+    pub(crate) native_vars: Vec<bool>,
+    pub(crate) xors: Vec<XorConstraint>,
+    pub(crate) xors_pending: bool,
+    pub(crate) xor_matrices: Vec<XorMatrix>,
+    pub(crate) xor_matrix_of: Vec<Option<usize>>,
+    pub(crate) xor_qhead: usize,
+    pub(crate) pb_constraints: Vec<PbConstraint>,
+    // the constraints of every literal
+    pub(crate) pb_occurrences: Vec<Vec<usize>>,
+    pub(crate) pb_qhead: usize,
+
+    pub(crate) assigns: Vec<Option<bool>>,
+    pub(crate) levels: Vec<usize>,
+    pub(crate) reasons: Vec<Option<ClauseRef>>,
+    pub(crate) trail: Vec<Literal>,
+    pub(crate) trail_lim: Vec<usize>,
+    pub(crate) qhead: usize,
+    pub(crate) seen: Vec<bool>,
+    pub(crate) level_stamps: Vec<u64>,
+    pub(crate) stamp: u64,
+
+    pub(crate) heuristic: Box<dyn DecisionHeuristic>,
+    pub(crate) phases: Vec<bool>,
+
+    pub(crate) model: Vec<bool>,
+    pub(crate) failed: Vec<Literal>,
+
+    pub(crate) proof: Option<Box<dyn ProofSink>>,
+    pub(crate) proof_error: Option<io::Error>,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Solver {
     pub fn new() -> Self {
-        Self {
-            formula: Cnf::new(),
-            models: vec![Model::new()],
-            literal_index: 0,
-        }
+        Self::with_config(SolverConfig::default())
+    }
+
+    pub fn with_config(config: SolverConfig) -> Self {
+        let mut solver = Self {
+            restart: RestartPolicy::new(config.restart),
+            config,
+            stats: Statistics::default(),
+            timer: None,
+            progress: None,
+            limits: Limits::default(),
+            interrupt: InterruptHandle::default(),
+            start: (0, 0),
+            stopped: None,
+
+            clauses: Vec::new(),
+            free_clauses: Vec::new(),
+            watches: vec![Vec::new(), Vec::new()],
+            reductions: 0,
+            next_reduce: 0,
+            next_inprocess: 0,
+            simplified_trail: 0,
+            num_vars: 0,
+            ok: true,
+
+            frozen: vec![false],
+            eliminated: vec![false],
+            elim_stack: Vec::new(),
+            preprocess_pending: false,
+
+            native_vars: vec![false],
+            xors: Vec::new(),
+            xors_pending: false,
+            xor_matrices: Vec::new(),
+            xor_matrix_of: vec![None],
+            xor_qhead: 0,
+            pb_constraints: Vec::new(),
+            pb_occurrences: vec![Vec::new(), Vec::new()],
+            pb_qhead: 0,
+
+            assigns: vec![None],
+            levels: vec![0],
+            reasons: vec![None],
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            qhead: 0,
+            seen: vec![false],
+            level_stamps: vec![0],
+            stamp: 0,
+
+            heuristic: Box::new(Vsids::default()),
+            phases: vec![false],
+
+            model: Vec::new(),
+            failed: Vec::new(),
+
+            proof: None,
+            proof_error: None,
+        };
+        solver.schedule_reduce();
+        solver.schedule_inprocess();
+        solver
     }
 
     pub fn new_literal(&mut self) -> Literal {
-        self.literal_index += 1;
-        Literal::new(self.literal_index, false)
+        self.reserve_vars(self.num_vars + 1);
+        Literal::new(self.num_vars, false)
+    }
+
+    pub fn set_decision_heuristic(&mut self, mut heuristic: Box<dyn DecisionHeuristic>) {
+        for var in 1..=self.num_vars {
+            heuristic.new_variable(var);
+            if self.eliminated[var] {
+                heuristic.remove(var);
+            }
+        }
+        self.heuristic = heuristic;
+    }
+
+    /// Makes sure the variables 1 to `num_vars` exist.
+    pub fn reserve_variables(&mut self, num_vars: usize) {
+        self.reserve_vars(num_vars);
+    }
+
+    /// Writes a DRAT proof of every UNSAT answer to `writer`.
+    pub fn set_proof<W: Write + 'static>(&mut self, writer: W, format: ProofFormat) {
+        self.set_proof_sink(Box::new(DratWriter::new(writer, format)));
+    }
+
+    pub fn set_proof_sink(&mut self, sink: Box<dyn ProofSink>) {
+        self.proof = Some(sink);
+    }
+
+    /// Flushes the proof and reports the first error that occurred while
+    /// writing it.
+    pub fn flush_proof(&mut self) -> io::Result<()> {
+        if let Some(err) = self.proof_error.take() {
+            return Err(err);
+        }
+        match &mut self.proof {
+            Some(proof) => proof.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Calls `callback` with the current statistics every `interval`
+    /// conflicts.
+    pub fn set_progress<F: FnMut(&Statistics) + 'static>(&mut self, interval: u64, callback: F) {
+        self.progress = Some((interval.max(1), Box::new(callback)));
+    }
+
+    /// Sets the limits applying to every following `check` call. A call
+    /// hitting one of them returns `SolveResult::Unknown` and leaves the
+    /// solver ready for the next call.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
     }
 
     pub fn set_formula(&mut self, formula: Cnf) {
-        self.formula = formula;
+        for clause in &formula.clauses {
+            self.add_clause(&clause.literals);
+        }
     }
 
     pub fn add_clause(&mut self, literals: &[Literal]) {
-        self.formula.add_clause(literals);
+        self.preprocess_pending = true;
+        self.add_clause_unscheduled(literals);
     }
 
-    pub fn check(&mut self) -> bool {
-        self.apply_unit_rule();
+    // adds a clause without preprocessing the formula again before the next
+    // check
+    pub(crate) fn add_clause_unscheduled(&mut self, literals: &[Literal]) {
+        if !self.ok {
+            return;
+        }
+        self.cancel_until(0);
+        for literal in literals {
+            self.reserve_vars(literal.var);
+            if self.eliminated[literal.var] {
+                self.restore(literal.var);
+            }
+        }
 
-        if self.formula.is_consistent() {
-            return true;
+        let mut clause = Vec::with_capacity(literals.len());
+        let mut shortened = false;
+        for &literal in literals {
+            match self.value(literal) {
+                Some(true) => return,
+                Some(false) => {
+                    shortened = true;
+                    continue;
+                }
+                None => {}
+            }
+            if clause.contains(&-literal) {
+                return;
+            }
+            if !clause.contains(&literal) {
+                clause.push(literal);
+            }
         }
-        if self.formula.has_empty_clause() {
-            return false;
+
+        if shortened {
+            self.log_add(&clause);
+        }
+
+        match clause.len() {
+            0 => self.ok = false,
+            1 => {
+                self.assign(clause[0], None);
+                if self.propagate().is_some() {
+                    self.log_add(&[]);
+                    self.ok = false;
+                }
+            }
+            _ => {
+                self.attach_clause(clause, None);
+            }
         }
+    }
+
+    pub fn check(&mut self) -> SolveResult {
+        self.check_with_assumptions(&[])
+    }
+
+    /// Solves the formula under the given assumptions, which only hold for
+    /// this call. Clauses (including learned ones) are kept, so more clauses
+    /// can be added and the solver called again afterwards.
+    pub fn check_with_assumptions(&mut self, assumptions: &[Literal]) -> SolveResult {
+        self.timer = Some(Instant::now());
+        let result = self.search(assumptions);
+        self.update_elapsed();
+        self.timer = None;
+        result
+    }
+
+    fn search(&mut self, assumptions: &[Literal]) -> SolveResult {
+        self.failed.clear();
+        if !self.ok {
+            return SolveResult::Unsat;
+        }
+        self.cancel_until(0);
+        self.start = (self.stats.conflicts, self.stats.propagations);
+        self.stopped = None;
+        for literal in assumptions {
+            self.reserve_vars(literal.var);
+            if self.eliminated[literal.var] {
+                self.restore(literal.var);
+            }
+        }
+        if self.xors_pending {
+            self.build_xor_matrices();
+        }
+        if self.preprocess_pending && self.config.preprocess.enabled {
+            self.preprocess(assumptions);
+        }
+        if !self.ok {
+            return SolveResult::Unsat;
+        }
+
+        loop {
+            if let Some(reason) = self
+                .stopped
+                .take()
+                .or_else(|| self.limit_reached(self.start))
+            {
+                self.cancel_until(0);
+                return SolveResult::Unknown(reason);
+            }
+
+            let conflict = self.propagate_all();
+            if !self.ok {
+                return SolveResult::Unsat;
+            }
+            if let Some(conflict) = conflict {
+                if self.decision_level() == 0 {
+                    self.log_add(&[]);
+                    self.ok = false;
+                    return SolveResult::Unsat;
+                }
+
+                let (learnt, backtrack_level) = self.analyze(conflict);
+                let lbd = self.compute_lbd(&learnt);
+                self.log_add(&learnt);
+
+                self.stats.conflicts += 1;
+                self.stats.learned += 1;
+                self.restart.on_conflict(lbd);
+
+                self.cancel_until(backtrack_level);
+                match learnt.len() {
+                    1 => self.assign(learnt[0], None),
+                    _ => {
+                        let literal = learnt[0];
+                        let cref = self.attach_clause(learnt, Some(lbd));
+                        self.assign(literal, Some(cref));
+                    }
+                }
+
+                if self.stats.conflicts >= self.next_reduce {
+                    self.reduce_db();
+                    self.schedule_reduce();
+                }
+                self.report_progress();
+                continue;
+            }
+
+            if self.stats.conflicts >= self.next_inprocess {
+                self.cancel_until(0);
+                self.inprocess(assumptions);
+                self.schedule_inprocess();
+                if !self.ok {
+                    return SolveResult::Unsat;
+                }
+            }
+            if self.restart.should_restart() {
+                self.cancel_until(0);
+                self.restart.on_restart();
+                self.stats.restarts += 1;
+            }
+            if self.decision_level() == 0 {
+                self.simplify();
+            }
+
+            let mut next = None;
+            while self.decision_level() < assumptions.len() {
+                let literal = assumptions[self.decision_level()];
+                match self.value(literal) {
+                    Some(true) => self.trail_lim.push(self.trail.len()),
+                    Some(false) => {
+                        self.analyze_final(literal);
+                        self.cancel_until(0);
+                        return SolveResult::Unsat;
+                    }
+                    None => {
+                        next = Some(literal);
+                        break;
+                    }
+                }
+            }
+
+            match next.or_else(|| self.pick_branch_literal()) {
+                Some(literal) => {
+                    self.stats.decisions += 1;
+                    self.trail_lim.push(self.trail.len());
+                    self.assign(literal, None);
+                }
+                None => {
+                    self.model = self.assigns.iter().map(|v| v.unwrap_or(false)).collect();
+                    self.extend_model();
+                    self.cancel_until(0);
+                    return SolveResult::Sat;
+                }
+            }
+        }
+    }
+
+    /// Returns the assumptions responsible for the last `check_with_assumptions`
+    /// returning UNSAT. The result is empty if the formula is UNSAT on its own.
+    pub fn failed_assumptions(&self) -> Vec<Literal> {
+        self.failed.clone()
+    }
 
-        self.apply_splitting_rule()
+    pub fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> &Statistics {
+        &self.stats
     }
 
     pub fn model(&self) -> Model {
-        let mut model = Model::new();
+        (1..=self.num_vars)
+            .map(|var| (var, self.model.get(var).cloned().unwrap_or(false)))
+            .collect()
+    }
+
+    /// Whether preprocessing or inprocessing has to stop because a limit of
+    /// the running check was reached, which the check then returns.
+    pub(crate) fn should_stop(&mut self) -> bool {
+        if self.stopped.is_none() {
+            self.stopped = self.limit_reached(self.start);
+        }
+        self.stopped.is_some()
+    }
+
+    // `start` holds the conflicts and propagations at the start of the call
+    fn limit_reached(&self, start: (u64, u64)) -> Option<Reason> {
+        if self.interrupt.is_interrupted() {
+            self.interrupt.clear();
+            return Some(Reason::Interrupted);
+        }
 
-        for i in 1..=self.literal_index {
-            model.insert(i, false);
+        let limits = &self.limits;
+        if matches!(limits.conflicts, Some(max) if self.stats.conflicts - start.0 >= max) {
+            return Some(Reason::ConflictLimit);
+        }
+        if matches!(limits.propagations, Some(max) if self.stats.propagations - start.1 >= max) {
+            return Some(Reason::PropagationLimit);
+        }
+        if matches!(limits.deadline, Some(deadline) if Instant::now() >= deadline) {
+            return Some(Reason::Timeout);
         }
+        None
+    }
 
-        for m in &self.models {
-            model.extend(m);
+    fn update_elapsed(&mut self) {
+        if let Some(timer) = &mut self.timer {
+            let now = Instant::now();
+            self.stats.elapsed += now - *timer;
+            *timer = now;
         }
+    }
 
-        model
+    fn report_progress(&mut self) {
+        let conflicts = self.stats.conflicts;
+        if !matches!(&self.progress, Some((interval, _)) if conflicts.is_multiple_of(*interval)) {
+            return;
+        }
+        self.update_elapsed();
+        if let Some((_, callback)) = &mut self.progress {
+            callback(&self.stats);
+        }
     }
 
-    fn apply_unit_rule(&mut self) {
-        for literal in self.formula.find_unit_clauses() {
-            self.formula.remove_clauses_which_has(&literal);
-            self.formula.remove_from_all(&literal.inverted());
-            self.cur_model().insert(literal.var, !literal.inverted);
+    fn reserve_vars(&mut self, var: Variable) {
+        if var <= self.num_vars {
+            return;
+        }
+
+        for v in (self.num_vars + 1)..=var {
+            self.heuristic.new_variable(v);
         }
+        self.num_vars = var;
+        self.assigns.resize(var + 1, None);
+        self.levels.resize(var + 1, 0);
+        self.reasons.resize(var + 1, None);
+        self.seen.resize(var + 1, false);
+        self.frozen.resize(var + 1, false);
+        self.eliminated.resize(var + 1, false);
+        self.native_vars.resize(var + 1, false);
+        self.xor_matrix_of.resize(var + 1, None);
+        self.phases.resize(var + 1, false);
+        self.watches.resize((var + 1) * 2, Vec::new());
+        self.pb_occurrences.resize((var + 1) * 2, Vec::new());
     }
 
-    fn apply_splitting_rule(&mut self) -> bool {
-        let literal = match self.formula.head_literal() {
-            Some(l) => l,
-            None => return false,
+    // `lbd` is given for learned clauses only
+    pub(crate) fn attach_clause(
+        &mut self,
+        literals: Vec<Literal>,
+        lbd: Option<usize>,
+    ) -> ClauseRef {
+        let clause = StoredClause {
+            learnt: lbd.is_some(),
+            lbd: lbd.unwrap_or(0),
+            tier: self.tier_of(lbd.unwrap_or(0)),
+            used: false,
+            deleted: false,
+            literals,
+        };
+        let cref = match self.free_clauses.pop() {
+            Some(cref) => {
+                self.clauses[cref] = clause;
+                cref
+            }
+            None => {
+                self.clauses.push(clause);
+                self.clauses.len() - 1
+            }
         };
 
-        let original = self.formula.clone();
+        self.watch_clause(cref);
+        cref
+    }
+
+    pub(crate) fn watch_clause(&mut self, cref: ClauseRef) {
+        let literals = &self.clauses[cref].literals;
+        self.watches[literals[0].index()].push(Watcher {
+            cref,
+            blocker: literals[1],
+        });
+        self.watches[literals[1].index()].push(Watcher {
+            cref,
+            blocker: literals[0],
+        });
+    }
+
+    pub(crate) fn log_add(&mut self, literals: &[Literal]) {
+        if let Some(proof) = &mut self.proof {
+            if let Err(err) = proof.add(literals) {
+                self.proof_error.get_or_insert(err);
+            }
+        }
+    }
+
+    pub(crate) fn log_delete(&mut self, literals: &[Literal]) {
+        if let Some(proof) = &mut self.proof {
+            if let Err(err) = proof.delete(literals) {
+                self.proof_error.get_or_insert(err);
+            }
+        }
+    }
+
+    pub(crate) fn value(&self, literal: Literal) -> Option<bool> {
+        lit_value(&self.assigns, literal)
+    }
+
+    pub(crate) fn decision_level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    pub(crate) fn assign(&mut self, literal: Literal, reason: Option<ClauseRef>) {
+        self.assigns[literal.var] = Some(!literal.inverted);
+        self.levels[literal.var] = self.decision_level();
+        self.reasons[literal.var] = reason;
+        self.trail.push(literal);
+    }
+
+    pub(crate) fn cancel_until(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+
+        let lim = self.trail_lim[level];
+        for literal in self.trail.drain(lim..) {
+            self.assigns[literal.var] = None;
+            self.reasons[literal.var] = None;
+            self.phases[literal.var] = !literal.inverted;
+            self.heuristic.unassign(literal.var);
+        }
+        self.trail_lim.truncate(level);
+        self.qhead = self.trail.len();
+        self.xor_qhead = self.xor_qhead.min(self.trail.len());
+        self.pb_qhead = self.pb_qhead.min(self.trail.len());
+    }
+
+    // two-watched-literal propagation: watches[l] holds the clauses watching l,
+    // which are visited only when l becomes false
+    pub(crate) fn propagate(&mut self) -> Option<ClauseRef> {
+        while self.qhead < self.trail.len() {
+            let false_literal = -self.trail[self.qhead];
+            self.qhead += 1;
+            self.stats.propagations += 1;
+
+            let mut watchers = std::mem::take(&mut self.watches[false_literal.index()]);
+            let mut conflict = None;
+            let mut i = 0;
+            let mut j = 0;
 
-        self.formula.add_clause(&[literal]);
-        self.push_model();
-        if self.check() {
-            return true;
+            while i < watchers.len() {
+                let watcher = watchers[i];
+                i += 1;
+                if self.value(watcher.blocker) == Some(true) {
+                    watchers[j] = watcher;
+                    j += 1;
+                    continue;
+                }
+
+                let cref = watcher.cref;
+                let literals = &mut self.clauses[cref].literals;
+                if literals[0] == false_literal {
+                    literals.swap(0, 1);
+                }
+
+                let first = literals[0];
+                let watcher = Watcher {
+                    cref,
+                    blocker: first,
+                };
+                if lit_value(&self.assigns, first) == Some(true) {
+                    watchers[j] = watcher;
+                    j += 1;
+                    continue;
+                }
+
+                let assigns = &self.assigns;
+                let new_watch = literals[2..]
+                    .iter()
+                    .position(|&l| lit_value(assigns, l) != Some(false));
+                if let Some(k) = new_watch.map(|k| k + 2) {
+                    literals.swap(1, k);
+                    self.watches[literals[1].index()].push(watcher);
+                    continue;
+                }
+
+                watchers[j] = watcher;
+                j += 1;
+                match self.value(first) {
+                    Some(false) => {
+                        conflict = Some(cref);
+                        self.qhead = self.trail.len();
+                        while i < watchers.len() {
+                            watchers[j] = watchers[i];
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+                    _ => self.assign(first, Some(cref)),
+                }
+            }
+
+            watchers.truncate(j);
+            self.watches[false_literal.index()] = watchers;
+            if conflict.is_some() {
+                return conflict;
+            }
         }
-        self.pop_model();
-        self.formula = original.clone();
 
-        self.formula.add_clause(&[-literal]);
-        self.push_model();
-        if self.check() {
-            return true;
+        None
+    }
+
+    /// Propagates the clauses and the other constraints until nothing more is
+    /// implied. A conflict at decision level 0 makes the formula UNSAT
+    /// without a conflicting clause.
+    pub(crate) fn propagate_all(&mut self) -> Option<ClauseRef> {
+        loop {
+            if let Some(conflict) = self.propagate() {
+                return Some(conflict);
+            }
+            let assigned = self.trail.len();
+            let conflict = self.propagate_pbs();
+            if conflict.is_some() || !self.ok {
+                return conflict;
+            }
+            if self.trail.len() > assigned {
+                continue;
+            }
+            let conflict = self.propagate_xors();
+            if conflict.is_some() || self.trail.len() == assigned || !self.ok {
+                return conflict;
+            }
         }
-        self.pop_model();
-        self.formula = original;
+    }
 
-        false
+    /// Assigns `implied` as another kind of constraint derived it from the
+    /// `falsified` literals. Above level 0 the derivation is learned as the
+    /// reason clause.
+    pub(crate) fn explain_implied(&mut self, mut falsified: Vec<Literal>, implied: Literal) {
+        if self.decision_level() == 0 {
+            self.assign(implied, None);
+            return;
+        }
+        // implications without falsified literals are found at level 0
+        debug_assert!(!falsified.is_empty());
+        falsified.sort_by_key(|l| Reverse(self.levels[l.var]));
+        // the implied literal counts as a level of its own
+        let lbd = self.compute_lbd(&falsified) + 1;
+        falsified.insert(0, implied);
+        let cref = self.attach_clause(falsified, Some(lbd));
+        self.assign(implied, Some(cref));
     }
 
-    fn push_model(&mut self) {
-        self.models.push(Model::new());
+    /// Learns the clause of the `falsified` literals another kind of
+    /// constraint conflicts on, watching the two of the highest levels.
+    pub(crate) fn explain_conflict(&mut self, mut falsified: Vec<Literal>) -> Option<ClauseRef> {
+        if self.decision_level() == 0 {
+            self.log_add(&[]);
+            self.ok = false;
+            return None;
+        }
+        debug_assert!(falsified.len() >= 2);
+        falsified.sort_by_key(|l| Reverse(self.levels[l.var]));
+        let lbd = self.compute_lbd(&falsified);
+        Some(self.attach_clause(falsified, Some(lbd)))
     }
 
-    fn pop_model(&mut self) {
-        self.models.pop();
+    // 1UIP conflict analysis
+    fn analyze(&mut self, conflict: ClauseRef) -> (Vec<Literal>, usize) {
+        let mut learnt = vec![Literal::new(1, false)];
+        let mut counter = 0;
+        let mut index = self.trail.len();
+        let mut cref = conflict;
+        let mut skip = None;
+
+        loop {
+            if self.clauses[cref].learnt {
+                self.bump_clause(cref);
+            }
+            for &literal in &self.clauses[cref].literals {
+                if Some(literal) == skip {
+                    continue;
+                }
+                let var = literal.var;
+                if self.seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+
+                self.seen[var] = true;
+                self.heuristic.bump(var);
+                if self.levels[var] == self.decision_level() {
+                    counter += 1;
+                } else {
+                    learnt.push(literal);
+                }
+            }
+
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var] {
+                    break;
+                }
+            }
+            let literal = self.trail[index];
+            self.seen[literal.var] = false;
+            counter -= 1;
+
+            if counter == 0 {
+                learnt[0] = -literal;
+                break;
+            }
+            skip = Some(literal);
+            cref = self.reasons[literal.var].unwrap();
+        }
+
+        for literal in &learnt {
+            self.seen[literal.var] = false;
+        }
+        self.heuristic.on_conflict();
+
+        let mut backtrack_level = 0;
+        for i in 1..learnt.len() {
+            let level = self.levels[learnt[i].var];
+            if level > backtrack_level {
+                backtrack_level = level;
+                learnt.swap(1, i);
+            }
+        }
+
+        (learnt, backtrack_level)
     }
 
-    fn cur_model(&mut self) -> &mut Model {
-        self.models.last_mut().unwrap()
+    // literal block distance: the number of distinct decision levels in a clause
+    pub(crate) fn compute_lbd(&mut self, literals: &[Literal]) -> usize {
+        self.next_stamp();
+        count_levels(literals, &self.levels, &mut self.level_stamps, self.stamp)
+    }
+
+    pub(crate) fn compute_lbd_of(&mut self, cref: ClauseRef) -> usize {
+        self.next_stamp();
+        let literals = &self.clauses[cref].literals;
+        count_levels(literals, &self.levels, &mut self.level_stamps, self.stamp)
+    }
+
+    fn next_stamp(&mut self) {
+        // assumptions that are already satisfied open empty decision levels,
+        // so there can be more levels than variables
+        if self.level_stamps.len() <= self.decision_level() {
+            self.level_stamps.resize(self.decision_level() + 1, 0);
+        }
+        self.stamp += 1;
+    }
+
+    // collects the assumptions implying the negation of the falsified assumption
+    fn analyze_final(&mut self, literal: Literal) {
+        self.failed.push(literal);
+        if self.decision_level() == 0 {
+            return;
+        }
+
+        self.seen[literal.var] = true;
+        for i in (self.trail_lim[0]..self.trail.len()).rev() {
+            let var = self.trail[i].var;
+            if !self.seen[var] {
+                continue;
+            }
+
+            match self.reasons[var] {
+                None => self.failed.push(self.trail[i]),
+                Some(cref) => {
+                    for &l in &self.clauses[cref].literals[1..] {
+                        if self.levels[l.var] > 0 {
+                            self.seen[l.var] = true;
+                        }
+                    }
+                }
+            }
+            self.seen[var] = false;
+        }
+        self.seen[literal.var] = false;
+    }
+
+    fn pick_branch_literal(&mut self) -> Option<Literal> {
+        self.heuristic
+            .next(&self.assigns)
+            .map(|var| Literal::new(var, !self.phases[var]))
+    }
+}
+
+fn lit_value(assigns: &[Option<bool>], literal: Literal) -> Option<bool> {
+    assigns[literal.var].map(|v| v != literal.inverted)
+}
+
+fn count_levels(literals: &[Literal], levels: &[usize], stamps: &mut [u64], stamp: u64) -> usize {
+    let mut count = 0;
+    for literal in literals {
+        let level = levels[literal.var];
+        if stamps[level] != stamp {
+            stamps[level] = stamp;
+            count += 1;
+        }
     }
+    count
 }
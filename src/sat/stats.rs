@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    pub decisions: u64,
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub restarts: u64,
+    /// Learned clauses, including learned units.
+    pub learned: u64,
+    /// Clauses removed by reductions and level-0 simplification.
+    pub deleted: u64,
+    /// Learned clauses removed by reductions, also counted in `deleted`.
+    pub reduced: u64,
+    /// Variables removed by bounded variable elimination.
+    pub eliminated: u64,
+    /// Clauses removed by subsumption.
+    pub subsumed: u64,
+    /// Clauses shortened by self-subsuming resolution.
+    pub strengthened: u64,
+    /// Variables replaced by an equivalent literal.
+    pub substituted: u64,
+    /// Literals found false by probing.
+    pub failed_literals: u64,
+    /// Binary clauses added by probing.
+    pub hyper_binary: u64,
+    /// Literals implied by Gaussian elimination on XOR constraints.
+    pub xor_propagations: u64,
+    /// Literals implied by pseudo-Boolean constraints.
+    pub pb_propagations: u64,
+    /// Time spent inside `check` calls.
+    pub elapsed: Duration,
+}
@@ -13,12 +13,18 @@ pub struct Clause {
     pub literals: Vec<Literal>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Literal {
     pub var: Variable,
     pub inverted: bool,
 }
 
+impl Default for Cnf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Cnf {
     pub fn new() -> Self {
         Self {
@@ -57,7 +63,10 @@ impl Cnf {
     }
 
     pub fn head_literal(&self) -> Option<Literal> {
-        self.clauses.get(0).and_then(|c| c.literals.get(0)).cloned()
+        self.clauses
+            .first()
+            .and_then(|c| c.literals.first())
+            .cloned()
     }
 
     pub fn dump(&self) -> String {
@@ -66,7 +75,7 @@ impl Cnf {
         for clause in &self.clauses {
             for literal in &clause.literals {
                 if literal.inverted {
-                    buf.push_str("-");
+                    buf.push('-');
                 }
                 buf.push_str(&format!("{} ", literal.var));
                 num_of_var = num_of_var.max(literal.var);
@@ -109,6 +118,10 @@ impl Literal {
             inverted: !self.inverted,
         }
     }
+
+    pub(crate) fn index(&self) -> usize {
+        self.var * 2 + self.inverted as usize
+    }
 }
 
 impl Neg for Literal {
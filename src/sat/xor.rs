@@ -0,0 +1,349 @@
+use super::{
+    solver::{ClauseRef, Solver},
+    types::{Literal, Variable},
+};
+
+#[derive(Debug, Clone)]
+pub struct XorConfig {
+    /// Propagates XOR constraints by Gaussian elimination. Otherwise they are
+    /// encoded as clauses.
+    pub gauss: bool,
+    /// Connected XOR constraints beyond this many are encoded as clauses.
+    pub max_rows: usize,
+}
+
+impl Default for XorConfig {
+    fn default() -> Self {
+        Self {
+            gauss: true,
+            max_rows: 2000,
+        }
+    }
+}
+
+pub(crate) struct XorConstraint {
+    pub(crate) vars: Vec<Variable>,
+    pub(crate) rhs: bool,
+}
+
+// a sum of XOR constraints over the columns of its matrix
+#[derive(Clone)]
+struct Row {
+    bits: Vec<u64>,
+    rhs: bool,
+    // the column of an unassigned variable occurring in no other row
+    pivot: Option<usize>,
+}
+
+impl Row {
+    fn has(&self, column: usize) -> bool {
+        self.bits[column / 64] >> (column % 64) & 1 == 1
+    }
+
+    fn add(&mut self, other: &Row) {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a ^= b;
+        }
+        self.rhs ^= other.rhs;
+    }
+
+    fn columns(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word >> bit & 1 == 1)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+// The XOR constraints sharing variables, kept in echelon form with respect to
+// the unassigned variables. Every row either has a pivot or only assigned
+// variables, so a row with a single unassigned variable exists whenever the
+// assignment implies a variable.
+pub(crate) struct XorMatrix {
+    columns: Vec<Variable>,
+    rows: Vec<Row>,
+    assigned: Vec<u64>,
+    values: Vec<u64>,
+    pub(crate) dirty: bool,
+}
+
+enum Outcome {
+    Conflict(usize),
+    // rows with a single unassigned variable, their pivot
+    Implied(Vec<usize>),
+}
+
+impl XorMatrix {
+    fn new(columns: Vec<Variable>, rows: Vec<Row>) -> Self {
+        let words = columns.len().div_ceil(64);
+        Self {
+            columns,
+            rows,
+            assigned: vec![0; words],
+            values: vec![0; words],
+            dirty: true,
+        }
+    }
+
+    fn update(&mut self, assigns: &[Option<bool>]) -> Outcome {
+        self.assigned.fill(0);
+        self.values.fill(0);
+        for (column, &var) in self.columns.iter().enumerate() {
+            if let Some(value) = assigns[var] {
+                self.assigned[column / 64] |= 1 << (column % 64);
+                self.values[column / 64] |= (value as u64) << (column % 64);
+            }
+        }
+
+        // rows whose pivot got assigned pick another one, which is removed
+        // from the other rows
+        for i in 0..self.rows.len() {
+            let row = &self.rows[i];
+            if matches!(row.pivot, Some(pivot) if !self.is_assigned(pivot)) {
+                continue;
+            }
+            let pivot = row.columns().find(|&column| !self.is_assigned(column));
+            self.rows[i].pivot = pivot;
+            let Some(pivot) = pivot else {
+                continue;
+            };
+
+            let row = self.rows[i].clone();
+            for (j, other) in self.rows.iter_mut().enumerate() {
+                if j != i && other.has(pivot) {
+                    other.add(&row);
+                }
+            }
+        }
+
+        let mut implied = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            let mut unassigned = 0;
+            let mut parity = row.rhs;
+            for (w, &word) in row.bits.iter().enumerate() {
+                unassigned += (word & !self.assigned[w]).count_ones();
+                parity ^= (word & self.values[w]).count_ones() % 2 == 1;
+            }
+            match unassigned {
+                0 if parity => return Outcome::Conflict(i),
+                1 => implied.push(i),
+                _ => {}
+            }
+        }
+        Outcome::Implied(implied)
+    }
+
+    fn is_assigned(&self, column: usize) -> bool {
+        self.assigned[column / 64] >> (column % 64) & 1 == 1
+    }
+}
+
+impl Solver {
+    /// Adds the constraint that an odd number of `literals` is true if `rhs`
+    /// holds, and an even number otherwise.
+    ///
+    /// XOR constraints are propagated by Gaussian elimination next to the
+    /// clauses. DRAT cannot express that reasoning, so while a proof is being
+    /// written they are encoded as clauses instead, which the proof takes as
+    /// part of the input. Set the proof before adding any.
+    pub fn add_xor(&mut self, literals: &[Literal], rhs: bool) {
+        if !self.ok {
+            return;
+        }
+        self.cancel_until(0);
+        self.preprocess_pending = true;
+
+        let mut rhs = rhs;
+        let mut vars = Vec::with_capacity(literals.len());
+        for literal in literals {
+            self.reserve_variables(literal.var);
+            if self.eliminated[literal.var] {
+                self.restore(literal.var);
+            }
+            rhs ^= literal.inverted;
+            match self.assigns[literal.var] {
+                Some(value) => rhs ^= value,
+                None => vars.push(literal.var),
+            }
+        }
+        // a variable occurring twice cancels out
+        vars.sort_unstable();
+        let mut i = 0;
+        while i < vars.len() {
+            if i + 1 < vars.len() && vars[i] == vars[i + 1] {
+                vars.drain(i..i + 2);
+            } else {
+                i += 1;
+            }
+        }
+
+        if vars.len() <= 2 || self.proof.is_some() || !self.config.xor.gauss {
+            self.encode_xor(vars, rhs);
+            return;
+        }
+        for &var in &vars {
+            self.native_vars[var] = true;
+        }
+        self.xors.push(XorConstraint { vars, rhs });
+        self.xors_pending = true;
+    }
+
+    // cuts the constraint into pieces of at most four variables, chained by
+    // fresh variables, and adds every clause excluding a wrong parity
+    pub(crate) fn encode_xor(&mut self, mut vars: Vec<Variable>, rhs: bool) {
+        while vars.len() > 4 {
+            let link = self.new_literal().var;
+            let mut piece = vars.drain(..3).collect::<Vec<_>>();
+            piece.push(link);
+            self.encode_xor(piece, false);
+            vars.insert(0, link);
+        }
+
+        for assignment in 0..1u32 << vars.len() {
+            if (assignment.count_ones() % 2 == 1) == rhs {
+                continue;
+            }
+            let clause = vars
+                .iter()
+                .enumerate()
+                .map(|(i, &var)| Literal::new(var, assignment >> i & 1 == 1))
+                .collect::<Vec<_>>();
+            self.add_clause(&clause);
+        }
+    }
+
+    /// Groups the XOR constraints into matrices of connected constraints.
+    pub(crate) fn build_xor_matrices(&mut self) {
+        self.xors_pending = false;
+
+        // union-find over the variables
+        let mut parent = (0..=self.num_vars).collect::<Vec<_>>();
+        for xor in &self.xors {
+            for &var in &xor.vars[1..] {
+                let (a, b) = (find(&mut parent, xor.vars[0]), find(&mut parent, var));
+                parent[a] = b;
+            }
+        }
+
+        let mut components = vec![Vec::new(); self.num_vars + 1];
+        for (i, xor) in self.xors.iter().enumerate() {
+            components[find(&mut parent, xor.vars[0])].push(i);
+        }
+
+        self.xor_matrices.clear();
+        self.xor_matrix_of = vec![None; self.num_vars + 1];
+        let mut encoded = vec![false; self.xors.len()];
+        let mut column_of = vec![0; self.num_vars + 1];
+        for component in components.into_iter().filter(|c| !c.is_empty()) {
+            if component.len() > self.config.xor.max_rows {
+                for i in component {
+                    encoded[i] = true;
+                }
+                continue;
+            }
+
+            let mut columns = component
+                .iter()
+                .flat_map(|&i| self.xors[i].vars.iter().cloned())
+                .collect::<Vec<_>>();
+            columns.sort_unstable();
+            columns.dedup();
+            for (column, &var) in columns.iter().enumerate() {
+                column_of[var] = column;
+                self.xor_matrix_of[var] = Some(self.xor_matrices.len());
+            }
+
+            let rows = component
+                .iter()
+                .map(|&i| {
+                    let xor = &self.xors[i];
+                    let mut bits = vec![0; columns.len().div_ceil(64)];
+                    for &var in &xor.vars {
+                        bits[column_of[var] / 64] |= 1 << (column_of[var] % 64);
+                    }
+                    Row {
+                        bits,
+                        rhs: xor.rhs,
+                        pivot: None,
+                    }
+                })
+                .collect();
+            self.xor_matrices.push(XorMatrix::new(columns, rows));
+        }
+
+        let xors = std::mem::take(&mut self.xors);
+        for (xor, encoded) in xors.into_iter().zip(encoded) {
+            if encoded {
+                self.encode_xor(xor.vars, xor.rhs);
+            } else {
+                self.xors.push(xor);
+            }
+        }
+    }
+
+    pub(crate) fn propagate_xors(&mut self) -> Option<ClauseRef> {
+        if self.xor_matrices.is_empty() {
+            return None;
+        }
+        while self.xor_qhead < self.trail.len() {
+            let var = self.trail[self.xor_qhead].var;
+            self.xor_qhead += 1;
+            if let Some(m) = self.xor_matrix_of[var] {
+                self.xor_matrices[m].dirty = true;
+            }
+        }
+
+        for m in 0..self.xor_matrices.len() {
+            let matrix = &mut self.xor_matrices[m];
+            if !matrix.dirty {
+                continue;
+            }
+            matrix.dirty = false;
+
+            match matrix.update(&self.assigns) {
+                Outcome::Conflict(row) => {
+                    let (falsified, _) = self.explain_row(m, row);
+                    return self.explain_conflict(falsified);
+                }
+                Outcome::Implied(rows) => {
+                    for row in rows {
+                        let (falsified, implied) = self.explain_row(m, row);
+                        self.stats.xor_propagations += 1;
+                        self.explain_implied(falsified, implied.unwrap());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // the falsified literals of a row under the current assignment and the
+    // literal it implies if a single variable is unassigned
+    fn explain_row(&self, m: usize, row: usize) -> (Vec<Literal>, Option<Literal>) {
+        let matrix = &self.xor_matrices[m];
+        let row = &matrix.rows[row];
+        let mut falsified = Vec::new();
+        let mut unassigned = None;
+        let mut parity = row.rhs;
+        for column in row.columns() {
+            let var = matrix.columns[column];
+            match self.assigns[var] {
+                Some(value) => {
+                    parity ^= value;
+                    falsified.push(Literal::new(var, value));
+                }
+                None => unassigned = Some(var),
+            }
+        }
+        (falsified, unassigned.map(|var| Literal::new(var, !parity)))
+    }
+}
+
+fn find(parent: &mut [Variable], mut var: Variable) -> Variable {
+    while parent[var] != var {
+        parent[var] = parent[parent[var]];
+        var = parent[var];
+    }
+    var
+}
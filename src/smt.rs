@@ -2,7 +2,6 @@ pub mod ast;
 pub use ast::*;
 
 pub mod bit_blaster;
-pub use bit_blaster::*;
 
 pub mod bit_vector;
 pub use bit_vector::*;
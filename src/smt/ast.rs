@@ -5,7 +5,10 @@ pub type Problem = Vec<Statement>;
 #[derive(Debug)]
 pub enum Statement {
     Assert(Expression),
+    AssertNamed(String, Expression),
+    CheckSat,
     Declare(String, VariableType),
+    GetUnsatCore,
 }
 
 #[derive(Debug)]
@@ -14,7 +17,7 @@ pub enum VariableType {
     BitVector(usize),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Expression {
     Constant(usize, usize),
     Variable(String),
@@ -53,7 +56,10 @@ impl fmt::Display for Statement {
         use self::Statement::*;
         match self {
             Assert(expr) => write!(f, "(assert {})", expr),
+            AssertNamed(name, expr) => write!(f, "(assert (! {} :named {}))", expr, name),
+            CheckSat => write!(f, "(check-sat)"),
             Declare(name, typ) => write!(f, "(declare-fun {} () {})", name, typ),
+            GetUnsatCore => write!(f, "(get-unsat-core)"),
         }
     }
 }
@@ -313,13 +313,8 @@ impl Solver {
         src2: Literal,
         prev_carry: Literal,
     ) {
-        let s1 = self.next_literal().as_bool();
-        let c1 = self.next_literal().as_bool();
-        let c2 = self.next_literal().as_bool();
-
-        tseytin::half_adder(self, s1, c1, src1, src2);
-        tseytin::half_adder(self, sum, c2, s1, prev_carry);
-        tseytin::or(self, carry, c1, c2);
+        tseytin::xor_many(self, sum, &[src1, src2, prev_carry]);
+        tseytin::majority(self, carry, src1, src2, prev_carry);
     }
 
     fn next_literal(&mut self) -> BitVector {
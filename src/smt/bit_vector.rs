@@ -24,6 +24,10 @@ impl BitVector {
         self.literals.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty()
+    }
+
     pub fn at(&self, i: usize) -> Literal {
         self.literals[i]
     }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::sat;
 
@@ -6,9 +6,56 @@ use super::{ast::Expression, bit_vector::BitVector};
 
 pub type Model = HashMap<String, usize>;
 
+/// The models of an `smt::Solver` projected onto some of its variables, as
+/// returned by [`Solver::enumerate_models`].
+pub struct Models<'a> {
+    models: sat::Models<'a>,
+    variables: Vec<(String, BitVector)>,
+}
+
+impl Models<'_> {
+    pub fn result(&self) -> Option<sat::SolveResult> {
+        self.models.result()
+    }
+}
+
+impl Iterator for Models<'_> {
+    type Item = Model;
+
+    fn next(&mut self) -> Option<Model> {
+        let model = self.models.next()?;
+        Some(
+            self.variables
+                .iter()
+                .map(|(name, bv)| (name.clone(), bv.get_int(&model)))
+                .collect(),
+        )
+    }
+}
+
+// an asserted expression, whose clauses only hold while `group` is assumed
+// if it has one
+pub(crate) struct Assertion {
+    pub(crate) expr: Expression,
+    pub(crate) name: Option<String>,
+    pub(crate) group: Option<sat::Literal>,
+}
+
 pub struct Solver {
     pub(crate) sat_solver: sat::Solver,
     pub(crate) variables: HashMap<String, BitVector>,
+    pub(crate) assertions: Vec<Assertion>,
+    // whether unnamed assertions get a group too
+    pub(crate) track_assertions: bool,
+    // the literals of the expressions assumed so far, so that assuming them
+    // again adds no clauses
+    pub(crate) assumed: HashMap<Expression, sat::Literal>,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Solver {
@@ -16,29 +63,170 @@ impl Solver {
         Self {
             sat_solver: sat::Solver::new(),
             variables: HashMap::new(),
+            assertions: Vec::new(),
+            track_assertions: false,
+            assumed: HashMap::new(),
         }
     }
 
     pub fn new_variable<S: Into<String>>(&mut self, name: S, len: usize) -> Expression {
         let name = name.into();
 
-        let literals = (0..len).map(|_| self.sat_solver.new_literal()).collect();
+        // later assertions refer to the bits, so they are never eliminated
+        let literals = (0..len)
+            .map(|_| {
+                let literal = self.sat_solver.new_literal();
+                self.sat_solver.freeze(literal.var);
+                literal
+            })
+            .collect();
         let bv = BitVector::new(literals);
         self.variables.insert(name.clone(), bv);
         Expression::Variable(name)
     }
 
+    /// Gives the later unnamed assertions a selector like the named ones, so
+    /// that `minimal_unsat_subset` can leave them out. Otherwise they are
+    /// added as plain clauses, which preprocessing can make use of.
+    pub fn set_track_assertions(&mut self, track: bool) {
+        self.track_assertions = track;
+    }
+
     pub fn assert(&mut self, expr: Expression) {
-        let val = self.transform(expr);
-        self.sat_solver.add_clause(&[val.as_bool()]);
+        self.add_assertion(expr, None);
+    }
+
+    /// Asserts `expr` under `name`, which `unsat_core` reports when the
+    /// assertion takes part in a conflict.
+    pub fn assert_named<S: Into<String>>(&mut self, name: S, expr: Expression) {
+        self.add_assertion(expr, Some(name.into()));
+    }
+
+    fn add_assertion(&mut self, expr: Expression, name: Option<String>) {
+        let val = self.transform(expr.clone());
+        let group = match name.is_some() || self.track_assertions {
+            true => {
+                let group = self.sat_solver.new_group();
+                self.sat_solver.add_group_clause(group, &[val.as_bool()]);
+                Some(group)
+            }
+            false => {
+                self.sat_solver.add_clause(&[val.as_bool()]);
+                None
+            }
+        };
+        self.assertions.push(Assertion { expr, name, group });
     }
 
     pub fn add_clause(&mut self, literals: &[sat::Literal]) {
         self.sat_solver.add_clause(literals);
     }
 
-    pub fn check(&mut self) -> bool {
-        self.sat_solver.check()
+    pub fn add_xor(&mut self, literals: &[sat::Literal], rhs: bool) {
+        self.sat_solver.add_xor(literals, rhs);
+    }
+
+    pub fn set_limits(&mut self, limits: sat::Limits) {
+        self.sat_solver.set_limits(limits);
+    }
+
+    pub fn interrupt_handle(&self) -> sat::InterruptHandle {
+        self.sat_solver.interrupt_handle()
+    }
+
+    pub fn check(&mut self) -> sat::SolveResult {
+        self.check_with_assumptions(&[])
+    }
+
+    pub fn check_with_assumptions(&mut self, exprs: &[Expression]) -> sat::SolveResult {
+        let mut assumptions = self.asserted();
+        for expr in exprs {
+            let literal = match self.assumed.get(expr) {
+                Some(&literal) => literal,
+                None => {
+                    let literal = self.transform(expr.clone()).as_bool();
+                    self.sat_solver.freeze(literal.var);
+                    self.assumed.insert(expr.clone(), literal);
+                    literal
+                }
+            };
+            assumptions.push(literal);
+        }
+        self.sat_solver.check_with_assumptions(&assumptions)
+    }
+
+    /// Returns a minimal subset of the named and tracked assertions that is
+    /// unsatisfiable together with the untracked ones, in the order they were
+    /// asserted, or None unless all of them together are unsatisfiable. See
+    /// [`Solver::set_track_assertions`] and
+    /// [`sat::Solver::minimal_unsat_subset`].
+    pub fn minimal_unsat_subset(
+        &mut self,
+        algorithm: sat::MusAlgorithm,
+    ) -> Option<Vec<Expression>> {
+        let groups = self.asserted();
+        let subset = self.sat_solver.minimal_unsat_subset(&groups, algorithm)?;
+        let exprs = self
+            .assertions
+            .iter()
+            .filter(|assertion| matches!(assertion.group, Some(group) if subset.contains(&group)))
+            .map(|assertion| assertion.expr.clone())
+            .collect();
+        Some(exprs)
+    }
+
+    /// Returns the names of the named assertions that the last check needed
+    /// to return UNSAT, in the order they were asserted. The unnamed ones are
+    /// left out, and the core is not necessarily minimal.
+    pub fn unsat_core(&self) -> Vec<String> {
+        let failed = self
+            .sat_solver
+            .failed_assumptions()
+            .into_iter()
+            .collect::<HashSet<_>>();
+        self.assertions
+            .iter()
+            .filter(|assertion| matches!(assertion.group, Some(group) if failed.contains(&group)))
+            .filter_map(|assertion| assertion.name.clone())
+            .collect()
+    }
+
+    // the groups of the asserted expressions, assumed by every check
+    fn asserted(&self) -> Vec<sat::Literal> {
+        self.assertions
+            .iter()
+            .filter_map(|assertion| assertion.group)
+            .collect()
+    }
+
+    /// Enumerates the models that differ on the variables named in
+    /// `projection`, each restricted to them, stopping after `limit` models if
+    /// given, or returns None if one of the names was never declared. See
+    /// [`sat::Solver::enumerate_models`].
+    pub fn enumerate_models(
+        &mut self,
+        projection: &[&str],
+        limit: Option<usize>,
+    ) -> Option<Models<'_>> {
+        let variables = projection
+            .iter()
+            .map(|&name| Some((name.to_string(), self.variables.get(name)?.clone())))
+            .collect::<Option<Vec<_>>>()?;
+        let vars = variables
+            .iter()
+            .flat_map(|(_, bv)| bv.literals.iter().map(|l| l.var))
+            .collect::<Vec<_>>();
+        let asserted = self.asserted();
+        Some(Models {
+            models: self
+                .sat_solver
+                .enumerate_models_with_assumptions(&vars, &asserted, limit),
+            variables,
+        })
+    }
+
+    pub fn stats(&self) -> &sat::Statistics {
+        self.sat_solver.stats()
     }
 
     pub fn model(&self) -> Model {
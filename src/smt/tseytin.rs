@@ -27,15 +27,25 @@ pub fn or_many(solver: &mut Solver, dst: Literal, src: &[Literal]) {
     solver.add_clause(&clause1);
 
     for l in src {
-        solver.add_clause(&[-l.clone(), dst]);
+        solver.add_clause(&[-*l, dst]);
     }
 }
 
 pub fn xor(solver: &mut Solver, dst: Literal, src1: Literal, src2: Literal) {
-    solver.add_clause(&[-src1, -src2, -dst]);
-    solver.add_clause(&[src1, src2, -dst]);
-    solver.add_clause(&[src1, -src2, dst]);
-    solver.add_clause(&[-src1, src2, dst]);
+    solver.add_xor(&[dst, src1, src2], false);
+}
+
+pub fn xor_many(solver: &mut Solver, dst: Literal, src: &[Literal]) {
+    let mut literals = src.to_vec();
+    literals.push(dst);
+    solver.add_xor(&literals, false);
+}
+
+pub fn majority(solver: &mut Solver, dst: Literal, src1: Literal, src2: Literal, src3: Literal) {
+    for (a, b) in [(src1, src2), (src1, src3), (src2, src3)] {
+        solver.add_clause(&[-a, -b, dst]);
+        solver.add_clause(&[a, b, -dst]);
+    }
 }
 
 pub fn half_adder(solver: &mut Solver, sum: Literal, carry: Literal, src1: Literal, src2: Literal) {
@@ -0,0 +1,159 @@
+use lutrix::{
+    parser::dimacs::{self, DimacsErrorKind, Mode},
+    sat::{types::Literal, ApproxConfig, ApproxError, ModelCounter, Natural},
+};
+
+fn lit(value: isize) -> Literal {
+    Literal::new(value.unsigned_abs(), value < 0)
+}
+
+fn counter(num_vars: usize, clauses: &[&[isize]]) -> ModelCounter {
+    let mut counter = ModelCounter::new();
+    counter.reserve_variables(num_vars);
+    for clause in clauses {
+        counter.add_clause(&clause.iter().map(|&v| lit(v)).collect::<Vec<_>>());
+    }
+    counter
+}
+
+// the models of `clauses` over `vars` by trying every assignment
+fn brute_force(num_vars: usize, clauses: &[Vec<Literal>], vars: &[usize]) -> u64 {
+    let mut projected = std::collections::HashSet::new();
+    for m in 0..1u64 << num_vars {
+        let holds = |l: &Literal| (m >> (l.var - 1) & 1 == 1) != l.inverted;
+        if clauses.iter().all(|c| c.iter().any(holds)) {
+            projected.insert(vars.iter().map(|&v| m >> (v - 1) & 1).collect::<Vec<_>>());
+        }
+    }
+    projected.len() as u64
+}
+
+#[test]
+fn natural() {
+    let max = Natural::from(u64::MAX);
+    assert_eq!(
+        (&max * &max).to_string(),
+        "340282366920938463426481119284349108225"
+    );
+    assert_eq!((&max + &Natural::one()).to_string(), "18446744073709551616");
+    assert_eq!(
+        Natural::power_of_two(100).to_string(),
+        "1267650600228229401496703205376"
+    );
+    assert!((Natural::power_of_two(100).log10() - 30.103).abs() < 1e-3);
+    assert!(Natural::power_of_two(64) > max);
+    assert_eq!(Natural::zero().to_string(), "0");
+}
+
+#[test]
+fn exact() {
+    // x1 | x2 with x3 free, and an independent x4 != x5
+    let c = counter(5, &[&[1, 2], &[4, 5], &[-4, -5]]);
+    assert_eq!(c.count(), Some(Natural::from(12)));
+
+    let c = counter(3, &[&[1], &[-1, 2], &[-2]]);
+    assert!(c.count().unwrap().is_zero());
+
+    // 200 free variables
+    let c = counter(200, &[]);
+    assert_eq!(c.count(), Some(Natural::power_of_two(200)));
+}
+
+#[test]
+fn projected() {
+    // x3 = x1 & x2, counted on x3 and on x1
+    let mut c = counter(3, &[&[-3, 1], &[-3, 2], &[3, -1, -2]]);
+    c.add_projection(&[3]);
+    assert_eq!(c.count(), Some(Natural::from(2)));
+
+    let mut c = counter(3, &[&[-3, 1], &[-3, 2], &[3, -1, -2], &[3]]);
+    c.add_projection(&[1]);
+    assert_eq!(c.count(), Some(Natural::one()));
+}
+
+#[test]
+fn random() {
+    let mut state = 7u64;
+    let mut next = |n: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % n
+    };
+    for round in 0..100 {
+        let num_vars = 12;
+        let clauses = (0..next(40))
+            .map(|_| {
+                (0..3)
+                    .map(|_| Literal::new(1 + next(num_vars as u64) as usize, next(2) == 0))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut c = ModelCounter::new();
+        c.reserve_variables(num_vars);
+        for clause in &clauses {
+            c.add_clause(clause);
+        }
+        let vars = match round % 2 {
+            0 => (1..=num_vars).collect::<Vec<_>>(),
+            _ => (1..=num_vars).filter(|_| next(2) == 0).collect(),
+        };
+        if round % 2 == 1 {
+            c.add_projection(&vars);
+        }
+        let expected = brute_force(num_vars, &clauses, &vars);
+        assert_eq!(c.count(), Some(Natural::from(expected)));
+    }
+}
+
+#[test]
+fn approx() {
+    // 2^14 * 3 models; few enough are counted exactly
+    let c = counter(16, &[&[15, 16]]);
+    let estimate = c
+        .approx_count(&ApproxConfig::default())
+        .unwrap()
+        .to_string();
+    let estimate = estimate.parse::<f64>().unwrap();
+    assert!((estimate / 49152.0 - 1.0).abs() < 0.8);
+
+    let c = counter(6, &[&[1, 2]]);
+    assert_eq!(
+        c.approx_count(&ApproxConfig::default()),
+        Ok(Natural::from(48))
+    );
+}
+
+#[test]
+fn interrupt() {
+    let c = counter(3, &[&[1, 2], &[-2, 3]]);
+    let handle = c.interrupt_handle();
+    handle.interrupt();
+    assert_eq!(c.count(), None);
+    assert!(!handle.is_interrupted());
+    assert_eq!(c.count(), Some(Natural::from(4)));
+
+    // the enumeration runs on a SAT solver sharing the handle
+    handle.interrupt();
+    assert_eq!(
+        c.approx_count(&ApproxConfig::default()),
+        Err(ApproxError::Interrupted)
+    );
+    assert!(!handle.is_interrupted());
+}
+
+#[test]
+fn show() {
+    let input = "p cnf 4 2\nc p show 1 2 0\nc p show 4 0\n1 2 3 0\n-1 -4 0\n";
+    let mut c = ModelCounter::new();
+    dimacs::read(input.as_bytes(), &mut c, Mode::Strict).unwrap();
+    assert!(c.is_projected());
+    assert_eq!(c.count(), Some(Natural::from(6)));
+
+    let input = "p cnf 1 0\nc p show 1 x 0\n";
+    let err = dimacs::read(input.as_bytes(), &mut ModelCounter::new(), Mode::Strict).unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (2, 12, DimacsErrorKind::InvalidLiteral("x".into()))
+    );
+}
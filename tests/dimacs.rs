@@ -0,0 +1,159 @@
+use std::io::{BufReader, Cursor};
+
+use lutrix::{
+    parser::dimacs::{self, DimacsError, DimacsErrorKind, Mode},
+    sat::{SolveResult, Solver},
+};
+
+#[test]
+fn parse() {
+    let input = "c comment with a p token\nc p cnf 1 1\np cnf 3 2\n1 -2\n 3 0\n-1 0\n";
+    let cnf = dimacs::parse(input).unwrap();
+    assert_eq!(cnf.clauses.len(), 2);
+    assert_eq!(format!("{}", cnf), "(x1 || !x2 || x3) && (!x1)");
+}
+
+#[test]
+fn missing_header() {
+    let err = dimacs::parse("c no header\n1 2 0\n").unwrap_err();
+    assert_eq!(
+        err,
+        DimacsError {
+            line: 2,
+            column: 1,
+            kind: DimacsErrorKind::MissingHeader,
+        }
+    );
+}
+
+#[test]
+fn invalid_header() {
+    let err = dimacs::parse("p cnf x 1\n").unwrap_err();
+    assert_eq!(err.kind, DimacsErrorKind::InvalidHeader);
+    let err = dimacs::parse("p cnf 1 1\np cnf 1 1\n1 0\n").unwrap_err();
+    assert_eq!((err.line, err.kind), (2, DimacsErrorKind::DuplicateHeader));
+}
+
+#[test]
+fn invalid_literal() {
+    let err = dimacs::parse("p cnf 2 1\n1  foo 0\n").unwrap_err();
+    assert_eq!(
+        err,
+        DimacsError {
+            line: 2,
+            column: 4,
+            kind: DimacsErrorKind::InvalidLiteral("foo".into()),
+        }
+    );
+}
+
+#[test]
+fn clause_count() {
+    let input = "p cnf 2 1\n1 0\n2 0\n";
+    let err = dimacs::parse(input).unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (3, 3, DimacsErrorKind::TooManyClauses { expected: 1 })
+    );
+    assert_eq!(
+        dimacs::parse_with(input, Mode::Lenient)
+            .unwrap()
+            .clauses
+            .len(),
+        2
+    );
+
+    let input = "p cnf 2 3\n1 0\n2 0\n";
+    let err = dimacs::parse(input).unwrap_err();
+    assert_eq!(
+        err.kind,
+        DimacsErrorKind::TooFewClauses {
+            expected: 3,
+            found: 2
+        }
+    );
+    assert_eq!(
+        dimacs::parse_with(input, Mode::Lenient)
+            .unwrap()
+            .clauses
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn variable_out_of_range() {
+    let input = "p cnf 2 1\n1 -3 0\n";
+    let err = dimacs::parse(input).unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (2, 3, DimacsErrorKind::VariableOutOfRange { var: 3, max: 2 })
+    );
+    assert!(dimacs::parse_with(input, Mode::Lenient).is_ok());
+}
+
+#[test]
+fn too_many_variables() {
+    let max = dimacs::MAX_VARIABLES;
+    let mut solver = Solver::new();
+    let err = dimacs::read(
+        "p cnf 999999999999 1\n1 0\n".as_bytes(),
+        &mut solver,
+        Mode::Strict,
+    )
+    .unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (1, 1, DimacsErrorKind::TooManyVariables { max })
+    );
+
+    let input = format!("p cnf 2 1\n1 -{} 0\n", max + 1);
+    let err = dimacs::read(input.as_bytes(), &mut solver, Mode::Lenient).unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (2, 3, DimacsErrorKind::TooManyVariables { max })
+    );
+}
+
+#[test]
+fn lenient_end() {
+    let input = "p cnf 2 2\n1 2 0\n-1\n";
+    assert_eq!(
+        dimacs::parse(input).unwrap_err().kind,
+        DimacsErrorKind::UnterminatedClause
+    );
+    assert_eq!(
+        dimacs::parse_with(input, Mode::Lenient)
+            .unwrap()
+            .clauses
+            .len(),
+        2
+    );
+
+    let input = "p cnf 2 1\n1 2 0\n%\n0\n";
+    assert!(dimacs::parse(input).is_err());
+    assert_eq!(
+        dimacs::parse_with(input, Mode::Lenient)
+            .unwrap()
+            .clauses
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn read_into_solver() {
+    let input = "c streamed\np cnf 4 3\n1 2 0\n-1 0\n-2 3 0\n";
+    let mut s = Solver::new();
+    dimacs::read(BufReader::new(Cursor::new(input)), &mut s, Mode::Strict).unwrap();
+    assert_eq!(s.check(), SolveResult::Sat);
+
+    let model = s.model();
+    assert_eq!(model.len(), 4);
+    assert!(!model[&1] && model[&2] && model[&3]);
+
+    let input = "p cnf 1 2\n1 0\n-1 0\n";
+    let mut s = Solver::new();
+    dimacs::read(input.as_bytes(), &mut s, Mode::Strict).unwrap();
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
@@ -0,0 +1,124 @@
+use std::{cell::RefCell, rc::Rc};
+
+use lutrix::{
+    maxsat::{Algorithm, Solver},
+    parser::wcnf::{self, WcnfErrorKind},
+    sat::{types::Literal, SolveResult},
+};
+
+const ALGORITHMS: [Algorithm; 2] = [Algorithm::CoreGuided, Algorithm::Linear];
+
+// x1 + ... + x5 <= 2 as hard clauses, each x_i preferred with weight i
+fn at_most_two(algorithm: Algorithm) -> Solver {
+    let mut s = Solver::with_algorithm(algorithm);
+    let x = (0..5).map(|_| s.new_literal()).collect::<Vec<_>>();
+    for i in 0..5 {
+        for j in i + 1..5 {
+            for k in j + 1..5 {
+                s.add_clause(&[-x[i], -x[j], -x[k]]);
+            }
+        }
+    }
+    for (i, &x) in x.iter().enumerate() {
+        s.add_soft_clause(&[x], i as u64 + 1);
+    }
+    s
+}
+
+#[test]
+fn weighted() {
+    for algorithm in ALGORITHMS {
+        let mut s = at_most_two(algorithm);
+        assert_eq!(s.check(), SolveResult::Sat);
+        assert_eq!(s.cost(), Some(6));
+        let model = s.model().unwrap();
+        assert_eq!(
+            (1..=5).map(|var| model[&var]).collect::<Vec<_>>(),
+            [false, false, false, true, true]
+        );
+
+        // soft clauses can be added between calls
+        s.add_soft_clause(&[Literal::new(5, true), Literal::new(4, true)], 10);
+        s.add_soft_clause(&[], 2);
+        assert_eq!(s.check(), SolveResult::Sat);
+        assert_eq!(s.cost(), Some(2 + 1 + 2 + 4));
+    }
+}
+
+#[test]
+fn hard_unsat() {
+    for algorithm in ALGORITHMS {
+        let mut s = Solver::with_algorithm(algorithm);
+        let x = s.new_literal();
+        s.add_soft_clause(&[x], 1);
+        s.add_clause(&[x]);
+        s.add_clause(&[-x]);
+        assert_eq!(s.check(), SolveResult::Unsat);
+        assert_eq!(s.model(), None);
+    }
+}
+
+#[test]
+fn progress() {
+    for algorithm in ALGORITHMS {
+        let costs = Rc::new(RefCell::new(Vec::new()));
+        let mut s = at_most_two(algorithm);
+        let log = costs.clone();
+        s.set_progress(move |cost| log.borrow_mut().push(cost));
+        assert_eq!(s.check(), SolveResult::Sat);
+
+        let costs = costs.borrow();
+        assert_eq!(costs.last(), Some(&6));
+        assert!(costs.windows(2).all(|w| w[0] > w[1]));
+    }
+}
+
+#[test]
+fn wcnf_formats() {
+    let old = "c old format\np wcnf 3 5 10\n10 1 2 3 0\n10 -1 -2 0\n3 -1 0\n2 -2\n 0\n1 -3 0\n";
+    let new = "c new format\nh 1 2 3 0\nh -1 -2 0\n3 -1 0\n2 -2 0\n1 -3 0\n";
+    let old = wcnf::parse(old).unwrap();
+    assert_eq!(old, wcnf::parse(new).unwrap());
+    assert_eq!((old.num_vars, old.hard.len(), old.soft.len()), (3, 2, 3));
+
+    let mut s = Solver::new();
+    old.add_to(&mut s);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert_eq!(s.cost(), Some(1));
+}
+
+#[test]
+fn wcnf_errors() {
+    let err = wcnf::parse("p wcnf 3\n").unwrap_err();
+    assert_eq!(err.kind, WcnfErrorKind::InvalidHeader);
+    let err = wcnf::parse("h 1 0\nx 2 0\n").unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (2, 1, WcnfErrorKind::InvalidWeight("x".into()))
+    );
+    let err = wcnf::parse("3 1 two 0\n").unwrap_err();
+    assert_eq!(
+        (err.column, err.kind),
+        (5, WcnfErrorKind::InvalidLiteral("two".into()))
+    );
+    let err = wcnf::parse("h 1 2\n").unwrap_err();
+    assert_eq!(err.kind, WcnfErrorKind::UnterminatedClause);
+    let err = wcnf::parse("9223372036854775808 1 0\n9223372036854775808 2 0\n").unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (2, 1, WcnfErrorKind::WeightOverflow)
+    );
+}
+
+// weights and costs beyond i64 are still exact
+#[test]
+fn large_weights() {
+    let input = "h -1 -2 0\n9223372036854775808 1 0\n4611686018427387904 2 0\n5 3 0\nh -3 0\n";
+    let wcnf = wcnf::parse(input).unwrap();
+    for algorithm in ALGORITHMS {
+        let mut s = Solver::with_algorithm(algorithm);
+        wcnf.add_to(&mut s);
+        assert_eq!(s.check(), SolveResult::Sat);
+        assert_eq!(s.cost(), Some((1 << 62) + 5));
+    }
+}
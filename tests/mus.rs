@@ -0,0 +1,112 @@
+use lutrix::{
+    parser::gcnf::{self, GcnfErrorKind},
+    sat::{types::Literal, MusAlgorithm, SolveResult, Solver},
+};
+
+const ALGORITHMS: [MusAlgorithm; 2] = [MusAlgorithm::Deletion, MusAlgorithm::QuickXplain];
+
+fn lit(value: isize) -> Literal {
+    Literal::new(value.unsigned_abs(), value < 0)
+}
+
+const GCNF: &str = "c groups 1 to 4 have several minimal subsets, group 5 is in none
+p gcnf 2 6 5
+{0} 1 2 0
+{1} -1 0
+{2} 1 -2 0
+{3} -2 0
+{4} -1 2 0
+{4} 1 0
+{5} 1 2 0
+";
+
+#[test]
+fn parse() {
+    let gcnf = gcnf::parse(GCNF).unwrap();
+    assert_eq!(gcnf.num_vars, 2);
+    assert_eq!(gcnf.num_groups, 5);
+    assert_eq!(gcnf.clauses.len(), 7);
+    assert_eq!(gcnf.clauses[0], (0, vec![lit(1), lit(2)]));
+    assert_eq!(gcnf.clauses[5], (4, vec![lit(1)]));
+
+    let error = |input: &str| {
+        let error = gcnf::parse(input).unwrap_err();
+        (error.line, error.column, error.kind)
+    };
+    assert_eq!(error("{1} 1 0\n"), (1, 1, GcnfErrorKind::MissingHeader));
+    assert_eq!(error("p gcnf 2 1\n"), (1, 1, GcnfErrorKind::InvalidHeader));
+    assert_eq!(
+        error("p gcnf 2 1 1\n1 2 0\n"),
+        (2, 1, GcnfErrorKind::InvalidGroup("1".into()))
+    );
+    assert_eq!(
+        error("p gcnf 2 1 1\n{2} 1 0\n"),
+        (2, 1, GcnfErrorKind::GroupOutOfRange { group: 2, max: 1 })
+    );
+    assert_eq!(
+        error("p gcnf 2 1 1\n{1} 1 2\n"),
+        (2, 1, GcnfErrorKind::UnterminatedClause)
+    );
+}
+
+#[test]
+fn minimal_unsat_subset() {
+    for algorithm in ALGORITHMS {
+        let mut s = Solver::new();
+        let groups = gcnf::parse(GCNF).unwrap().add_to(&mut s);
+        let mus = s.minimal_unsat_subset(&groups, algorithm).unwrap();
+        assert!(!mus.is_empty() && !mus.contains(&groups[4]));
+        assert_eq!(s.check_with_assumptions(&mus), SolveResult::Unsat);
+        for i in 0..mus.len() {
+            let mut subset = mus.clone();
+            subset.remove(i);
+            assert_eq!(s.check_with_assumptions(&subset), SolveResult::Sat);
+        }
+
+        let satisfiable = [groups[1], groups[3], groups[4]];
+        assert_eq!(s.minimal_unsat_subset(&satisfiable, algorithm), None);
+    }
+}
+
+// the groups x_i and -x_i | x_i+1 chained to a final -x_n, whose only minimal
+// subset is every group
+#[test]
+fn chain() {
+    for algorithm in ALGORITHMS {
+        let mut s = Solver::new();
+        let x = (0..20).map(|_| s.new_literal()).collect::<Vec<_>>();
+        let mut groups = vec![s.new_group()];
+        s.add_group_clause(groups[0], &[x[0]]);
+        for i in 0..19 {
+            let group = s.new_group();
+            s.add_group_clause(group, &[-x[i], x[i + 1]]);
+            groups.push(group);
+        }
+        let last = s.new_group();
+        s.add_group_clause(last, &[-x[19]]);
+        groups.push(last);
+
+        // unrelated groups are dropped
+        let mut all = groups.clone();
+        for _ in 0..10 {
+            let group = s.new_group();
+            let y = s.new_literal();
+            s.add_group_clause(group, &[y]);
+            all.insert(5, group);
+        }
+        assert_eq!(s.minimal_unsat_subset(&all, algorithm), Some(groups));
+    }
+}
+
+#[test]
+fn unsat_without_groups() {
+    for algorithm in ALGORITHMS {
+        let mut s = Solver::new();
+        let group = s.new_group();
+        let x = s.new_literal();
+        s.add_group_clause(group, &[x]);
+        s.add_clause(&[x]);
+        s.add_clause(&[-x]);
+        assert_eq!(s.minimal_unsat_subset(&[group], algorithm), Some(vec![]));
+    }
+}
@@ -0,0 +1,102 @@
+use lutrix::{
+    parser::opb::{self, Constraint, OpbError, OpbErrorKind},
+    sat::{types::Literal, Comparison, SolveResult, Solver},
+};
+
+const INPUT: &str = "* #variable= 5 #constraint= 3
+min: +3 x1 +2 x2 +4 x3 +1 x4 ;
++1 x1 +1 x2 >= 1;
++1 x2 +1 ~x3
+  +1 x4 >= 2 ;
++2 x1 +1 x3 +1 x4 = 2;
+";
+
+#[test]
+fn parse() {
+    let problem = opb::parse(INPUT).unwrap();
+    assert_eq!(problem.num_vars, 5);
+    assert_eq!(
+        problem.objective,
+        Some(vec![
+            (3, Literal::new(1, false)),
+            (2, Literal::new(2, false)),
+            (4, Literal::new(3, false)),
+            (1, Literal::new(4, false)),
+        ])
+    );
+    assert_eq!(problem.constraints.len(), 3);
+    assert_eq!(
+        problem.constraints[1],
+        Constraint {
+            terms: vec![
+                (1, Literal::new(2, false)),
+                (1, Literal::new(3, true)),
+                (1, Literal::new(4, false)),
+            ],
+            cmp: Comparison::AtLeast,
+            rhs: 2,
+        }
+    );
+}
+
+#[test]
+fn errors() {
+    let err = opb::parse("+1 x1 +1 x2 x3 >= 1;\n").unwrap_err();
+    assert_eq!(
+        err,
+        OpbError {
+            line: 1,
+            column: 13,
+            kind: OpbErrorKind::NonLinearTerm,
+        }
+    );
+    let err = opb::parse("+1 x1\n+2 y2 >= 1;\n").unwrap_err();
+    assert_eq!(
+        (err.line, err.column, err.kind),
+        (2, 4, OpbErrorKind::InvalidLiteral("y2".into()))
+    );
+    let err = opb::parse("+1 x1 +1 x2;\n").unwrap_err();
+    assert_eq!(err.kind, OpbErrorKind::MissingRelation);
+    let err = opb::parse("+1 x1 >= 1\n").unwrap_err();
+    assert_eq!(err.kind, OpbErrorKind::UnterminatedConstraint);
+    let err = opb::parse("min: +1 x1;\nmin: +1 x2;\n").unwrap_err();
+    assert_eq!((err.line, err.kind), (2, OpbErrorKind::DuplicateObjective));
+}
+
+#[test]
+fn write() {
+    let mut problem = opb::parse(INPUT).unwrap();
+    problem.constraints.push(Constraint {
+        terms: vec![(2, Literal::new(5, true)), (-1, Literal::new(1, false))],
+        cmp: Comparison::AtMost,
+        rhs: 1,
+    });
+
+    let mut output = Vec::new();
+    opb::write(&problem, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("-2 ~x5 +1 x1 >= -1 ;\n"));
+
+    // `<=` is written as `>=`, otherwise the problem is unchanged
+    let reread = opb::parse(&output).unwrap();
+    assert_eq!(reread.objective, problem.objective);
+    assert_eq!(reread.constraints[..3], problem.constraints[..3]);
+    assert_eq!(reread.constraints[3].cmp, Comparison::AtLeast);
+}
+
+#[test]
+fn optimize() {
+    let problem = opb::parse(INPUT).unwrap();
+    let objective = problem.objective.clone().unwrap();
+    let mut solver = Solver::new();
+    problem.add_to(&mut solver);
+
+    let mut best = None;
+    while solver.check() == SolveResult::Sat {
+        let cost = problem.cost(&solver.model()).unwrap();
+        assert!(best.is_none_or(|best| cost < best));
+        best = Some(cost);
+        solver.add_pb(&objective, Comparison::AtMost, cost - 1);
+    }
+    assert_eq!(best, Some(5));
+}
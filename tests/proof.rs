@@ -0,0 +1,164 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use lutrix::sat::{proof::*, Cnf, Literal, SolveResult, Solver};
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn lit(value: isize) -> Literal {
+    Literal::new(value.unsigned_abs(), value < 0)
+}
+
+fn cnf(clauses: &[&[isize]]) -> Cnf {
+    let mut cnf = Cnf::new();
+    for clause in clauses {
+        cnf.add_clause(&clause.iter().map(|&v| lit(v)).collect::<Vec<_>>());
+    }
+    cnf
+}
+
+fn pigeonhole(holes: usize) -> Cnf {
+    let var = |p: usize, h: usize| (p * holes + h + 1) as isize;
+    let mut cnf = Cnf::new();
+    for p in 0..=holes {
+        cnf.add_clause(&(0..holes).map(|h| lit(var(p, h))).collect::<Vec<_>>());
+    }
+    for h in 0..holes {
+        for p in 0..=holes {
+            for q in (p + 1)..=holes {
+                cnf.add_clause(&[lit(-var(p, h)), lit(-var(q, h))]);
+            }
+        }
+    }
+    cnf
+}
+
+fn random_3sat(seed: u64, vars: usize, clauses: usize) -> Cnf {
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut cnf = Cnf::new();
+    for _ in 0..clauses {
+        let clause = (0..3)
+            .map(|_| Literal::new(1 + (next() % vars as u64) as usize, next() % 2 == 0))
+            .collect::<Vec<_>>();
+        cnf.add_clause(&clause);
+    }
+    cnf
+}
+
+// solves `cnf` and checks the proof of every UNSAT answer
+fn check(cnf: &Cnf, format: ProofFormat) -> bool {
+    let buffer = SharedBuffer::default();
+    let mut s = Solver::new();
+    s.set_proof(buffer.clone(), format);
+    s.set_formula(cnf.clone());
+
+    let result = s.check() == SolveResult::Sat;
+    s.flush_proof().unwrap();
+    if !result {
+        let proof = buffer.0.borrow();
+        assert_eq!(detect_format(&proof), format);
+        assert_eq!(check_drat(cnf, &proof), Ok(()));
+    }
+    result
+}
+
+#[test]
+fn drat_pigeonhole() {
+    assert!(!check(&pigeonhole(5), ProofFormat::Text));
+    assert!(!check(&pigeonhole(5), ProofFormat::Binary));
+}
+
+#[test]
+fn drat_random() {
+    let mut unsat = 0;
+    for seed in 1..=40 {
+        let cnf = random_3sat(seed * 7919, 30, 150);
+        let format = match seed % 2 {
+            0 => ProofFormat::Text,
+            _ => ProofFormat::Binary,
+        };
+        if !check(&cnf, format) {
+            unsat += 1;
+        }
+    }
+    assert!(unsat > 0);
+}
+
+#[test]
+fn drat_handwritten() {
+    let f = cnf(&[&[1, 2], &[1, -2], &[-1, 2], &[-1, -2]]);
+    assert_eq!(check_drat(&f, b"1 0\n0\n"), Ok(()));
+    assert_eq!(
+        check_drat(&f, b"c comment with p\n1 0\nd 1 2 0\n0\n"),
+        Ok(())
+    );
+    assert_eq!(check_drat(&f, b"1 0\n"), Ok(()));
+    assert_eq!(check_drat(&f, b""), Err(ProofError::NoConflict));
+
+    let g = cnf(&[&[1, 2], &[-1, 2]]);
+    assert_eq!(
+        check_drat(&g, b"-2 0\n0\n"),
+        Err(ProofError::InvalidLemma {
+            step: 1,
+            clause: vec![lit(-2)],
+        })
+    );
+    assert!(matches!(
+        check_drat(&g, b"1 x 0\n"),
+        Err(ProofError::Syntax { position: 1, .. })
+    ));
+}
+
+#[test]
+fn drat_rat() {
+    // x3 is a fresh variable, so (x3 || x1) is RAT on x3
+    let f = cnf(&[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+    assert_eq!(check_drat(&f, b"3 1 0\n1 0\n0\n"), Ok(()));
+
+    // (-x4 || -x2) is RAT on -x4 only, and checking (-x4) propagates it first
+    let f = cnf(&[&[2], &[3, -1], &[-1, -3], &[1, -3], &[3, 1], &[4, -1]]);
+    assert_eq!(check_drat(&f, b"-4 -2 0\n-4 0\n0\n"), Ok(()));
+}
+
+#[test]
+fn lrat() {
+    let f = cnf(&[&[1, 2], &[1, -2], &[-1, 2], &[-1, -2]]);
+    assert_eq!(check_lrat(&f, b"5 1 0 1 2 0\n6 0 5 3 4 0\n"), Ok(()));
+    assert_eq!(
+        check_lrat(&f, b"5 1 0 1 2 0\n5 d 1 2 0\n6 0 5 3 4 0\n"),
+        Ok(())
+    );
+    assert_eq!(
+        check_lrat(&f, b"5 1 0 1 0\n"),
+        Err(ProofError::InvalidLemma {
+            step: 1,
+            clause: vec![lit(1)],
+        })
+    );
+    assert_eq!(
+        check_lrat(&f, b"5 1 0 7 0\n"),
+        Err(ProofError::UnknownClause { step: 1, id: 7 })
+    );
+    assert_eq!(
+        check_lrat(&f, b"5 1 0 1 2 0\n"),
+        Err(ProofError::NoConflict)
+    );
+}
@@ -1,9 +1,21 @@
-use lutrix::sat::solver::Solver;
+use std::{
+    cell::RefCell,
+    io,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use lutrix::sat::{
+    config::SolverConfig, heuristic::*, preprocess::PreprocessConfig, proof::ProofSink,
+    reduce::ReduceConfig, restart::RestartStrategy, solver::Solver, Comparison, Limits, Literal,
+    PbEncoding, Reason, SolveResult, XorConfig,
+};
 
 #[test]
 fn solver_1() {
     let mut s = Solver::new();
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -11,7 +23,7 @@ fn solver_2() {
     let mut s = Solver::new();
     let a = s.new_literal();
     s.add_clause(&[a]);
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -20,7 +32,7 @@ fn solver_3() {
     let a = s.new_literal();
     s.add_clause(&[a]);
     s.add_clause(&[-a]);
-    assert!(!s.check());
+    assert_eq!(s.check(), SolveResult::Unsat);
 }
 
 #[test]
@@ -29,7 +41,7 @@ fn solver_4() {
     let a = s.new_literal();
     let b = s.new_literal();
     s.add_clause(&[a, b]);
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -39,7 +51,7 @@ fn solver_5() {
     let b = s.new_literal();
     s.add_clause(&[a, -b]);
     s.add_clause(&[-a, b]);
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -52,5 +64,730 @@ fn solver_6() {
     s.add_clause(&[-a, c]);
     s.add_clause(&[-a, -c]);
     s.add_clause(&[-b, -c]);
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
+}
+
+#[test]
+fn solver_7() {
+    let mut s = Solver::new();
+    let a = s.new_literal();
+    let b = s.new_literal();
+    s.add_clause(&[a, b]);
+    s.add_clause(&[a, -b]);
+    s.add_clause(&[-a, b]);
+    s.add_clause(&[-a, -b]);
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
+
+#[test]
+fn pigeonhole() {
+    // 4 pigeons cannot sit in 3 holes
+    let mut s = Solver::new();
+    add_pigeonhole(&mut s, 3);
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
+
+fn add_pigeonhole(s: &mut Solver, holes: usize) {
+    for clause in pigeonhole_clauses(s, holes) {
+        s.add_clause(&clause);
+    }
+}
+
+// the pigeonhole clauses, each only holding while `selector` is assumed
+fn add_selected_pigeonhole(s: &mut Solver, holes: usize, selector: Literal) {
+    for mut clause in pigeonhole_clauses(s, holes) {
+        clause.push(-selector);
+        s.add_clause(&clause);
+    }
+}
+
+fn pigeonhole_clauses(s: &mut Solver, holes: usize) -> Vec<Vec<Literal>> {
+    // holes + 1 pigeons cannot sit in the holes
+    let p = (0..=holes)
+        .map(|_| (0..holes).map(|_| s.new_literal()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut clauses = p.clone();
+    for hole in 0..holes {
+        for (i, p1) in p.iter().enumerate() {
+            for p2 in &p[(i + 1)..] {
+                clauses.push(vec![-p1[hole], -p2[hole]]);
+            }
+        }
+    }
+    clauses
+}
+
+#[test]
+fn heuristics() {
+    let heuristics: Vec<Box<dyn DecisionHeuristic>> = vec![
+        Box::new(Vsids::default()),
+        Box::new(Vmtf::new()),
+        Box::new(FixedOrder::new()),
+    ];
+
+    for heuristic in heuristics {
+        let mut s = Solver::new();
+        s.set_decision_heuristic(heuristic);
+        add_pigeonhole(&mut s, 3);
+        assert_eq!(s.check(), SolveResult::Unsat);
+    }
+
+    // pure literals are eliminated, and eliminated variables are no decisions
+    let heuristics: [fn() -> Box<dyn DecisionHeuristic>; 3] = [
+        || Box::new(Vsids::default()),
+        || Box::new(Vmtf::new()),
+        || Box::new(FixedOrder::new()),
+    ];
+    for heuristic in heuristics {
+        let mut s = Solver::new();
+        s.set_decision_heuristic(heuristic());
+        let x = (0..20).map(|_| s.new_literal()).collect::<Vec<_>>();
+        for pair in x.windows(2) {
+            s.add_clause(pair);
+        }
+        assert_eq!(s.check(), SolveResult::Sat);
+        let stats = s.stats();
+        assert!(stats.eliminated > 0);
+        assert!(stats.decisions + stats.eliminated <= 20);
+
+        // restored variables are decided again
+        assert_eq!(s.check_with_assumptions(&[-x[5]]), SolveResult::Sat);
+        let model = s.model();
+        assert!(x.windows(2).all(|pair| pair.iter().any(|l| model[&l.var])));
+    }
+}
+
+#[test]
+fn restart_strategies() {
+    let strategies = vec![
+        RestartStrategy::None,
+        RestartStrategy::Geometric {
+            first: 4,
+            factor: 1.5,
+        },
+        RestartStrategy::Luby { unit: 4 },
+        RestartStrategy::Glucose {
+            window: 8,
+            margin: 0.8,
+        },
+    ];
+
+    for restart in strategies {
+        let mut s = Solver::with_config(SolverConfig {
+            restart,
+            ..Default::default()
+        });
+        // php(5) ends before the LBDs of the glucose window ever rise
+        add_pigeonhole(&mut s, 6);
+        assert_eq!(s.check(), SolveResult::Unsat);
+
+        match restart {
+            RestartStrategy::None => assert_eq!(s.stats().restarts, 0),
+            _ => assert!(s.stats().restarts > 0),
+        }
+    }
+}
+
+#[test]
+fn reduce_db() {
+    let solve = |first: u64| {
+        let mut s = Solver::with_config(SolverConfig {
+            reduce: ReduceConfig {
+                first,
+                increment: 5,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        add_pigeonhole(&mut s, 6);
+        assert_eq!(s.check(), SolveResult::Unsat);
+        s.stats().clone()
+    };
+
+    let stats = solve(10);
+    assert!(stats.reduced > 0);
+    assert!(stats.deleted >= stats.reduced);
+    assert_eq!(solve(u64::MAX).reduced, 0);
+}
+
+#[test]
+fn stats() {
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let mut s = Solver::new();
+    let recorded = reports.clone();
+    s.set_progress(100, move |stats| {
+        recorded.borrow_mut().push(stats.conflicts)
+    });
+    add_pigeonhole(&mut s, 6);
+    assert_eq!(s.check(), SolveResult::Unsat);
+
+    let stats = s.stats();
+    assert!(stats.conflicts > 0);
+    assert!(stats.decisions >= stats.conflicts);
+    assert!(stats.propagations > stats.decisions);
+    assert_eq!(stats.learned, stats.conflicts);
+    assert!(stats.elapsed > Default::default());
+
+    let reports = reports.borrow();
+    assert_eq!(reports.len() as u64, stats.conflicts / 100);
+    assert!(reports
+        .iter()
+        .enumerate()
+        .all(|(i, &c)| c == (i as u64 + 1) * 100));
+}
+
+#[test]
+fn limits() {
+    let mut s = Solver::new();
+    add_pigeonhole(&mut s, 6);
+
+    s.set_limits(Limits {
+        conflicts: Some(50),
+        ..Default::default()
+    });
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::ConflictLimit));
+    assert_eq!(s.stats().conflicts, 50);
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::ConflictLimit));
+    assert_eq!(s.stats().conflicts, 100);
+
+    s.set_limits(Limits {
+        propagations: Some(10),
+        ..Default::default()
+    });
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::PropagationLimit));
+
+    s.set_limits(Limits {
+        deadline: Some(Instant::now()),
+        ..Default::default()
+    });
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::Timeout));
+
+    s.set_limits(Limits::default());
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
+
+#[test]
+fn interrupt() {
+    let mut s = Solver::new();
+    add_pigeonhole(&mut s, 6);
+
+    let handle = s.interrupt_handle();
+    handle.interrupt();
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::Interrupted));
+    assert!(!handle.is_interrupted());
+
+    // preprocessing alone would refute this, but stops first
+    let mut s = Solver::new();
+    let (a, b) = (s.new_literal(), s.new_literal());
+    for clause in [[a, b], [a, -b], [-a, b], [-a, -b]] {
+        s.add_clause(&clause);
+    }
+    s.interrupt_handle().interrupt();
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::Interrupted));
+    assert_eq!(s.check(), SolveResult::Unsat);
+
+    // php(12) takes far longer than the interrupting thread waits
+    let mut s = Solver::new();
+    add_pigeonhole(&mut s, 12);
+    let handle = s.interrupt_handle();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::Interrupted));
+    interrupter.join().unwrap();
+}
+
+#[test]
+fn preprocess() {
+    // x1 = x2 & x3, x4 = x1 | x5, and x4 must hold
+    let clauses = [
+        vec![-1, 2],
+        vec![-1, 3],
+        vec![1, -2, -3],
+        vec![4, -1],
+        vec![4, -5],
+        vec![-4, 1, 5],
+        vec![4],
+        vec![-2, -5, 3],
+    ];
+    let to_literals = |clause: &[isize]| {
+        clause
+            .iter()
+            .map(|&l| Literal::new(l.unsigned_abs(), l < 0))
+            .collect::<Vec<_>>()
+    };
+
+    let mut s = Solver::new();
+    s.freeze(5);
+    for clause in &clauses {
+        s.add_clause(&to_literals(clause));
+    }
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(s.stats().eliminated > 0);
+    assert!(!s.is_eliminated(5));
+
+    let model = s.model();
+    let holds = |clause: &[isize]| clause.iter().any(|&l| model[&l.unsigned_abs()] == (l > 0));
+    assert!(clauses.iter().all(|clause| holds(clause)));
+
+    // adding clauses over eliminated variables brings them back
+    let eliminated = (1..=5)
+        .filter(|&var| s.is_eliminated(var))
+        .collect::<Vec<_>>();
+    assert!(!eliminated.is_empty());
+    let mut clauses = clauses.to_vec();
+    for &var in &eliminated {
+        s.add_clause(&[Literal::new(var, true)]);
+        clauses.push(vec![-(var as isize)]);
+    }
+    let satisfiable = (0..32).any(|bits: u32| {
+        let holds = |l: isize| (bits >> (l.unsigned_abs() - 1) & 1 == 1) == (l > 0);
+        clauses
+            .iter()
+            .all(|clause| clause.iter().any(|&l| holds(l)))
+    });
+    match satisfiable {
+        true => assert_eq!(s.check(), SolveResult::Sat),
+        false => assert_eq!(s.check(), SolveResult::Unsat),
+    }
+}
+
+#[test]
+fn equivalences() {
+    let mut s = Solver::new();
+    let x = (0..4).map(|_| s.new_literal()).collect::<Vec<_>>();
+    // x0 -> x1 -> x2 -> x0, x3 = !x0
+    s.add_clause(&[-x[0], x[1]]);
+    s.add_clause(&[-x[1], x[2]]);
+    s.add_clause(&[-x[2], x[0]]);
+    s.add_clause(&[x[3], x[0]]);
+    s.add_clause(&[-x[3], -x[2]]);
+    s.add_clause(&[x[1], x[3], -x[2]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(s.stats().substituted >= 3);
+
+    let model = s.model();
+    assert_eq!(model[&1], model[&2]);
+    assert_eq!(model[&1], model[&3]);
+    assert_ne!(model[&1], model[&4]);
+
+    s.add_clause(&[x[1]]);
+    s.add_clause(&[x[3]]);
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
+
+#[test]
+fn probing() {
+    let mut s = Solver::with_config(SolverConfig {
+        preprocess: PreprocessConfig {
+            elimination: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let x = (0..6).map(|_| s.new_literal()).collect::<Vec<_>>();
+    // x0 implies x1 and x2, which together imply x3
+    s.add_clause(&[-x[0], x[1]]);
+    s.add_clause(&[-x[0], x[2]]);
+    s.add_clause(&[-x[1], -x[2], x[3]]);
+    // x4 implies x5 and !x5
+    s.add_clause(&[-x[4], x[5]]);
+    s.add_clause(&[-x[4], -x[5]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(s.stats().hyper_binary > 0);
+    assert!(s.stats().failed_literals > 0);
+    assert!(!s.model()[&5]);
+}
+
+#[test]
+fn hyper_binary_resolution() {
+    let log = Rc::new(RefCell::new(ProofLog::default()));
+    let mut s = Solver::with_config(SolverConfig {
+        preprocess: PreprocessConfig {
+            elimination: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    s.set_proof_sink(Box::new(ProofRecorder(log.clone())));
+    let x = (0..7).map(|_| s.new_literal()).collect::<Vec<_>>();
+    // x0 implies x1, which implies x2 and x3, which together imply x4
+    s.add_clause(&[-x[0], x[1]]);
+    s.add_clause(&[-x[1], x[2]]);
+    s.add_clause(&[-x[1], x[3]]);
+    s.add_clause(&[-x[2], -x[3], x[4]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert_eq!(s.stats().hyper_binary, 1);
+    // x1 dominates x2 and x3, so the binary starts there and not at x0
+    assert!(log.borrow().added.contains(&vec![-x[1], x[4]]));
+
+    // probing again finds the same binary, which is not added twice
+    s.add_clause(&[x[5], x[6]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert_eq!(s.stats().hyper_binary, 1);
+}
+
+type Xor = (Vec<Literal>, bool);
+
+// random XORs over three of `vars` variables that `hidden` satisfies, and
+// their sum with the opposite parity
+fn parity_system(vars: usize, hidden: u64) -> (Vec<Xor>, Xor) {
+    let mut state = hidden | 1;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let value = |var: usize| hidden.rotate_left(var as u32 * 7) & 1 == 1;
+
+    let mut xors = Vec::new();
+    let mut sum = vec![false; vars + 1];
+    let mut sum_rhs = false;
+    for _ in 0..vars {
+        let literals = (0..3)
+            .map(|_| Literal::new(1 + (next() % vars as u64) as usize, next() % 2 == 0))
+            .collect::<Vec<_>>();
+        let rhs = literals
+            .iter()
+            .fold(false, |acc, l| acc ^ (value(l.var) != l.inverted));
+        for l in &literals {
+            sum[l.var] ^= true;
+            sum_rhs ^= l.inverted;
+        }
+        sum_rhs ^= rhs;
+        xors.push((literals, rhs));
+    }
+    let sum = (1..=vars)
+        .filter(|&var| sum[var])
+        .map(|var| Literal::new(var, false))
+        .collect();
+    (xors, (sum, !sum_rhs))
+}
+
+#[test]
+fn xor() {
+    for (vars, gauss) in [(200, true), (20, false)] {
+        let mut s = Solver::with_config(SolverConfig {
+            xor: XorConfig {
+                gauss,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let (xors, contradiction) = parity_system(vars, 0x9e37_79b9_7f4a_7c15);
+        s.reserve_variables(vars);
+        for (literals, rhs) in &xors {
+            s.add_xor(literals, *rhs);
+        }
+        assert_eq!(s.check(), SolveResult::Sat);
+        assert_eq!(s.stats().xor_propagations > 0, gauss);
+
+        let model = s.model();
+        for (literals, rhs) in &xors {
+            let parity = literals
+                .iter()
+                .fold(false, |acc, l| acc ^ (model[&l.var] != l.inverted));
+            assert_eq!(parity, *rhs);
+        }
+
+        s.add_xor(&contradiction.0, contradiction.1);
+        assert_eq!(s.check(), SolveResult::Unsat);
+    }
+}
+
+#[test]
+fn xor_assumptions() {
+    let mut s = Solver::new();
+    let x = (0..5).map(|_| s.new_literal()).collect::<Vec<_>>();
+    // x0 ^ x1 ^ x2 = 1 and x2 ^ x3 ^ x4 = 0, so x0 ^ x1 ^ x3 ^ x4 = 1
+    s.add_xor(&[x[0], x[1], x[2]], true);
+    s.add_xor(&[x[2], x[3], -x[4]], true);
+    assert_eq!(
+        s.check_with_assumptions(&[x[0], x[1], x[3], -x[4]]),
+        SolveResult::Sat
+    );
+    assert_eq!(
+        s.check_with_assumptions(&[x[0], -x[1], x[3], -x[4]]),
+        SolveResult::Unsat
+    );
+    let mut failed = s.failed_assumptions();
+    failed.sort_by_key(|l| l.var);
+    assert_eq!(failed, vec![x[0], -x[1], x[3], -x[4]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+}
+
+const ENCODINGS: [PbEncoding; 4] = [
+    PbEncoding::Native,
+    PbEncoding::SequentialCounter,
+    PbEncoding::Totalizer,
+    PbEncoding::CardinalityNetwork,
+];
+
+fn with_encoding(encoding: PbEncoding) -> Solver {
+    Solver::with_config(SolverConfig {
+        pb: encoding,
+        ..Default::default()
+    })
+}
+
+// checks every assignment of the first `vars` variables by assumptions
+fn check_all(s: &mut Solver, vars: usize, holds: impl Fn(u32) -> bool) {
+    for bits in 0..1u32 << vars {
+        let assumptions = (0..vars)
+            .map(|i| Literal::new(i + 1, bits >> i & 1 == 0))
+            .collect::<Vec<_>>();
+        let expected = match holds(bits) {
+            true => SolveResult::Sat,
+            false => SolveResult::Unsat,
+        };
+        assert_eq!(s.check_with_assumptions(&assumptions), expected);
+    }
+}
+
+#[test]
+fn cardinality() {
+    for encoding in ENCODINGS {
+        for k in 0..=6 {
+            let mut s = with_encoding(encoding);
+            let x = (0..6).map(|_| s.new_literal()).collect::<Vec<_>>();
+            s.add_at_most(&x, k);
+            s.add_at_least(&x[1..], k.saturating_sub(2));
+            check_all(&mut s, 6, |bits| {
+                bits.count_ones() as usize <= k && (bits >> 1).count_ones() as usize + 2 >= k
+            });
+        }
+    }
+}
+
+#[test]
+fn pseudo_boolean() {
+    let terms: [(i64, isize); 5] = [(3, 1), (-2, 2), (5, -3), (1, 4), (4, -1)];
+    let value = |bits: u32| -> i64 {
+        terms
+            .iter()
+            .filter(|&&(_, l)| (bits >> (l.unsigned_abs() - 1) & 1 == 1) == (l > 0))
+            .map(|&(a, _)| a)
+            .sum()
+    };
+    let terms = terms
+        .iter()
+        .map(|&(a, l)| (a, Literal::new(l.unsigned_abs(), l < 0)))
+        .collect::<Vec<_>>();
+
+    for encoding in ENCODINGS {
+        for rhs in -3..12 {
+            for cmp in [Comparison::AtMost, Comparison::AtLeast, Comparison::Equal] {
+                let mut s = with_encoding(encoding);
+                s.reserve_variables(4);
+                s.add_pb(&terms, cmp, rhs);
+                check_all(&mut s, 4, |bits| match cmp {
+                    Comparison::AtMost => value(bits) <= rhs,
+                    Comparison::AtLeast => value(bits) >= rhs,
+                    Comparison::Equal => value(bits) == rhs,
+                });
+            }
+        }
+    }
+}
+
+#[test]
+fn native_pigeonhole() {
+    let holes = 7;
+    let mut s = Solver::new();
+    let x = (0..=holes)
+        .map(|_| (0..holes).map(|_| s.new_literal()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    for pigeon in &x {
+        s.add_at_least(pigeon, 1);
+    }
+    for h in 0..holes {
+        let hole = x.iter().map(|pigeon| pigeon[h]).collect::<Vec<_>>();
+        s.add_at_most(&hole, 1);
+    }
+    assert_eq!(s.check(), SolveResult::Unsat);
+    assert!(s.stats().pb_propagations > 0);
+}
+
+#[test]
+fn model() {
+    let mut s = Solver::new();
+    let a = s.new_literal();
+    let b = s.new_literal();
+    let c = s.new_literal();
+    s.add_clause(&[a, b]);
+    s.add_clause(&[-a, c]);
+    s.add_clause(&[-b, -c]);
+    s.add_clause(&[-c, -a, b]);
+    assert_eq!(s.check(), SolveResult::Sat);
+
+    let m = s.model();
+    assert!(m[&a.var] || m[&b.var]);
+    assert!(!m[&a.var] || m[&c.var]);
+    assert!(!m[&b.var] || !m[&c.var]);
+}
+
+#[test]
+fn implication_chain() {
+    let mut s = Solver::new();
+    let xs = (0..50).map(|_| s.new_literal()).collect::<Vec<_>>();
+    for w in xs.windows(3) {
+        s.add_clause(&[-w[0], -w[1], w[2]]);
+    }
+    s.add_clause(&[xs[0]]);
+    s.add_clause(&[xs[1]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+
+    let m = s.model();
+    assert!(xs.iter().all(|x| m[&x.var]));
+}
+
+#[test]
+fn incremental() {
+    let mut s = Solver::new();
+    let a = s.new_literal();
+    let b = s.new_literal();
+    let c = s.new_literal();
+    s.add_clause(&[a, b, c]);
+    assert_eq!(s.check(), SolveResult::Sat);
+
+    s.add_clause(&[-a]);
+    s.add_clause(&[-b]);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(s.model()[&c.var]);
+
+    s.add_clause(&[-c]);
+    assert_eq!(s.check(), SolveResult::Unsat);
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
+
+#[test]
+fn assumptions() {
+    let mut s = Solver::new();
+    let a = s.new_literal();
+    let b = s.new_literal();
+    let c = s.new_literal();
+    s.add_clause(&[-a, b]);
+    s.add_clause(&[-b, c]);
+
+    assert_eq!(s.check_with_assumptions(&[a]), SolveResult::Sat);
+    assert!(s.model()[&c.var]);
+    assert_eq!(s.check_with_assumptions(&[a, -c]), SolveResult::Unsat);
+    assert_eq!(s.check_with_assumptions(&[-c]), SolveResult::Sat);
+    assert!(!s.model()[&a.var]);
+    assert_eq!(s.check_with_assumptions(&[a, a, b, b, c]), SolveResult::Sat);
+    assert_eq!(s.check(), SolveResult::Sat);
+}
+
+#[test]
+fn assumptions_keep_learnts() {
+    let mut s = Solver::new();
+    let selector = s.new_literal();
+    add_selected_pigeonhole(&mut s, 6, selector);
+
+    assert_eq!(s.check_with_assumptions(&[selector]), SolveResult::Unsat);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(!s.model()[&selector.var]);
+
+    let conflicts = s.stats().conflicts;
+    assert_eq!(s.check_with_assumptions(&[selector]), SolveResult::Unsat);
+    assert!(s.stats().conflicts - conflicts <= 1);
+}
+
+#[test]
+fn enumerate_models() {
+    // x1 | x2 | x3 with x4 free
+    let mut s = Solver::new();
+    let x = (0..4).map(|_| s.new_literal()).collect::<Vec<_>>();
+    s.add_clause(&x[..3]);
+
+    let mut models = s.enumerate_models(&[1, 2, 3], None);
+    let mut found = models
+        .by_ref()
+        .map(|m| (1..=3).map(|var| m[&var]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(models.result(), Some(SolveResult::Unsat));
+    drop(models);
+    found.sort();
+    found.dedup();
+    assert_eq!(found.len(), 7);
+    assert!(!found.contains(&vec![false; 3]));
+
+    assert_eq!(s.enumerate_models(&[4], None).count(), 2);
+    let mut models = s.enumerate_models(&[1, 2, 3, 4], Some(5));
+    assert_eq!(models.by_ref().count(), 5);
+    assert_eq!(models.result(), None);
+    drop(models);
+
+    // the blocking clauses are gone afterwards
+    s.add_clause(&[-x[0]]);
+    s.add_clause(&[-x[1]]);
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert_eq!(s.enumerate_models(&[3], None).count(), 1);
+    s.add_clause(&[-x[2]]);
+    assert_eq!(s.enumerate_models(&[3], None).count(), 0);
+}
+
+#[test]
+fn failed_assumptions() {
+    let mut s = Solver::new();
+    let a = s.new_literal();
+    let b = s.new_literal();
+    let c = s.new_literal();
+    let d = s.new_literal();
+    s.add_clause(&[-a, b]);
+    s.add_clause(&[-b, -c]);
+
+    assert_eq!(s.check_with_assumptions(&[d, a, c]), SolveResult::Unsat);
+    let mut failed = s.failed_assumptions();
+    failed.sort_by_key(|l| l.var);
+    assert_eq!(failed, vec![a, c]);
+
+    assert_eq!(s.check_with_assumptions(&[d, a]), SolveResult::Sat);
+    assert!(s.failed_assumptions().is_empty());
+
+    s.add_clause(&[-d]);
+    assert_eq!(s.check_with_assumptions(&[d, a]), SolveResult::Unsat);
+    assert_eq!(s.failed_assumptions(), vec![d]);
+}
+
+#[derive(Default)]
+struct ProofLog {
+    added: Vec<Vec<Literal>>,
+    deleted: Vec<Vec<Literal>>,
+}
+
+struct ProofRecorder(Rc<RefCell<ProofLog>>);
+
+impl ProofSink for ProofRecorder {
+    fn add(&mut self, literals: &[Literal]) -> io::Result<()> {
+        self.0.borrow_mut().added.push(literals.to_vec());
+        Ok(())
+    }
+
+    fn delete(&mut self, literals: &[Literal]) -> io::Result<()> {
+        self.0.borrow_mut().deleted.push(literals.to_vec());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn proof() {
+    let log = Rc::new(RefCell::new(ProofLog::default()));
+    let mut s = Solver::new();
+    s.set_proof_sink(Box::new(ProofRecorder(log.clone())));
+    add_pigeonhole(&mut s, 4);
+    assert_eq!(s.check(), SolveResult::Unsat);
+    assert!(s.flush_proof().is_ok());
+
+    let log = log.borrow();
+    assert!(log.added.len() > 1);
+    assert_eq!(log.added.last(), Some(&vec![]));
+    assert!(log.deleted.iter().all(|c| !c.is_empty()));
 }
@@ -1,4 +1,6 @@
-use lutrix::smt::Solver;
+use lutrix::parser::smtlib::{self, SmtlibErrorKind};
+use lutrix::sat::{Limits, MusAlgorithm, Reason, SolveResult};
+use lutrix::smt::{ast, Expression, Solver, Statement};
 use lutrix::{int, op};
 
 #[test]
@@ -9,7 +11,7 @@ fn bvnot() {
     s.assert(op!(= a, int!(0b01, 2)));
     s.assert(op!(= a, op!(!b)));
     s.assert(op!(= b, int!(0b10, 2)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -22,7 +24,7 @@ fn bvand() {
     s.assert(op!(= b, int!(0b0101, 4)));
     s.assert(op!(= c, op!(& a, b)));
     s.assert(op!(= c, int!(0b0001, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -35,7 +37,7 @@ fn bvor() {
     s.assert(op!(= b, int!(0b0101, 4)));
     s.assert(op!(= c, op!(| a, b)));
     s.assert(op!(= c, int!(0b0111, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -48,7 +50,7 @@ fn bvxor() {
     s.assert(op!(= b, int!(0b0101, 4)));
     s.assert(op!(= c, op!(^ a, b)));
     s.assert(op!(= c, int!(0b0110, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -61,7 +63,7 @@ fn bvadd() {
     s.assert(op!(= b, int!(0b0001, 4)));
     s.assert(op!(= c, op!(+ a, b)));
     s.assert(op!(= c, int!(0b1000, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -74,7 +76,7 @@ fn bvsub() {
     s.assert(op!(= b, int!(0b0001, 4)));
     s.assert(op!(= c, op!(- a, b)));
     s.assert(op!(= c, int!(0b0011, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 #[test]
 fn bvmul() {
@@ -86,7 +88,7 @@ fn bvmul() {
     s.assert(op!(= b, int!(0b0100, 4)));
     s.assert(op!(= c, op!(* a, b)));
     s.assert(op!(= c, int!(0b1100, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -97,7 +99,7 @@ fn bvshl() {
     s.assert(op!(= a, int!(0b0011, 4)));
     s.assert(op!(= b, op!(<< a, 3)));
     s.assert(op!(= b, int!(0b1000, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 #[test]
 fn bvshr() {
@@ -107,7 +109,7 @@ fn bvshr() {
     s.assert(op!(= a, int!(0b0011, 4)));
     s.assert(op!(= b, op!(>> a, 1)));
     s.assert(op!(= b, int!(0b0001, 4)));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -116,7 +118,7 @@ fn bvult() {
     let c0 = int!(0, 1);
     let c1 = int!(1, 1);
     s.assert(op!(< c0, c1));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -124,7 +126,7 @@ fn bvule() {
     let mut s = Solver::new();
     let c1 = int!(1, 1);
     s.assert(op!(<= c1, c1));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -133,7 +135,7 @@ fn bvugt() {
     let c0 = int!(0, 1);
     let c1 = int!(1, 1);
     s.assert(op!(> c1, c0));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
 }
 
 #[test]
@@ -141,5 +143,239 @@ fn bvuge() {
     let mut s = Solver::new();
     let c1 = int!(1, 1);
     s.assert(op!(>= c1, c1));
-    assert!(s.check());
+    assert_eq!(s.check(), SolveResult::Sat);
+}
+
+#[test]
+fn bvmul_16() {
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 16);
+    let b = s.new_variable("x2", 16);
+    s.assert(op!(= op!(* a, b), int!(0x8f3d, 16)));
+    s.assert(op!(> a, int!(1, 16)));
+    s.assert(op!(> b, int!(1, 16)));
+    assert_eq!(s.check(), SolveResult::Sat);
+
+    let model = s.model();
+    assert_eq!(model["x1"].wrapping_mul(model["x2"]) & 0xffff, 0x8f3d);
+}
+
+#[test]
+fn check_with_assumptions() {
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 4);
+    let b = s.new_variable("x2", 4);
+    s.assert(op!(= op!(+ a, b), int!(0b1010, 4)));
+
+    assert_eq!(
+        s.check_with_assumptions(&[op!(= a, int!(0b0011, 4))]),
+        SolveResult::Sat
+    );
+    assert_eq!(s.model()["x2"], 0b0111);
+    assert_eq!(
+        s.check_with_assumptions(&[op!(= a, int!(0b0011, 4)), op!(= b, int!(0b0011, 4))]),
+        SolveResult::Unsat
+    );
+    assert_eq!(
+        s.check_with_assumptions(&[op!(= b, int!(0b0011, 4))]),
+        SolveResult::Sat
+    );
+    assert_eq!(s.model()["x1"], 0b0111);
+}
+
+#[test]
+fn stats() {
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 8);
+    let b = s.new_variable("x2", 8);
+    s.assert(op!(= op!(* a, b), int!(143, 8)));
+    s.assert(op!(> a, int!(1, 8)));
+    s.assert(op!(> b, int!(1, 8)));
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(s.stats().decisions > 0);
+    assert!(s.stats().propagations > 0);
+}
+
+#[test]
+fn limits() {
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 16);
+    s.assert(op!(= op!(* a, a), int!(2, 16)));
+
+    s.set_limits(Limits {
+        conflicts: Some(1),
+        ..Default::default()
+    });
+    assert_eq!(s.check(), SolveResult::Unknown(Reason::ConflictLimit));
+
+    s.set_limits(Limits::default());
+    assert_eq!(s.check(), SolveResult::Unsat);
+}
+
+#[test]
+fn enumerate_models() {
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 3);
+    let b = s.new_variable("x2", 3);
+    s.new_variable("x3", 3);
+    s.assert(op!(= op!(+ a, b), int!(5, 3)));
+
+    let mut sums = s
+        .enumerate_models(&["x1", "x2"], None)
+        .unwrap()
+        .map(|m| {
+            assert_eq!(m.len(), 2);
+            assert_eq!((m["x1"] + m["x2"]) % 8, 5);
+            m["x1"]
+        })
+        .collect::<Vec<_>>();
+    sums.sort();
+    assert_eq!(sums, (0..8).collect::<Vec<_>>());
+    assert_eq!(s.enumerate_models(&["x3"], Some(3)).unwrap().count(), 3);
+    assert!(s.enumerate_models(&["x1", "y"], None).is_none());
+    assert_eq!(s.check(), SolveResult::Sat);
+}
+
+#[test]
+fn minimal_unsat_subset() {
+    for algorithm in [MusAlgorithm::Deletion, MusAlgorithm::QuickXplain] {
+        let mut s = Solver::new();
+        s.set_track_assertions(true);
+        let a = s.new_variable("x1", 4);
+        let b = s.new_variable("x2", 4);
+        let c = s.new_variable("x3", 4);
+        s.assert(op!(= a, int!(3, 4)));
+        s.assert(op!(= c, int!(7, 4)));
+        s.assert(op!(= b, op!(+ a, int!(1, 4))));
+        s.assert(op!(= b, int!(5, 4)));
+        assert_eq!(s.check(), SolveResult::Unsat);
+        assert_eq!(
+            s.minimal_unsat_subset(algorithm),
+            Some(vec![
+                op!(= a, int!(3, 4)),
+                op!(= b, op!(+ a, int!(1, 4))),
+                op!(= b, int!(5, 4))
+            ])
+        );
+    }
+
+    // untracked assertions hold in every subset
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 4);
+    let b = s.new_variable("x2", 4);
+    s.assert(op!(= a, int!(3, 4)));
+    s.assert_named("b", op!(= b, int!(5, 4)));
+    s.assert_named("c", op!(= b, op!(+ a, int!(2, 4))));
+    s.assert_named("d", op!(= b, op!(+ a, int!(1, 4))));
+    assert_eq!(
+        s.minimal_unsat_subset(MusAlgorithm::Deletion),
+        Some(vec![op!(= b, int!(5, 4)), op!(= b, op!(+ a, int!(1, 4)))])
+    );
+
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 4);
+    s.assert(op!(= a, int!(3, 4)));
+    assert_eq!(s.minimal_unsat_subset(MusAlgorithm::Deletion), None);
+}
+
+#[test]
+fn unsat_core() {
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 4);
+    let b = s.new_variable("x2", 4);
+    let c = s.new_variable("x3", 4);
+    s.assert_named("a", op!(= a, int!(3, 4)));
+    s.assert_named("c", op!(= c, int!(7, 4)));
+    s.assert(op!(= b, op!(+ a, int!(1, 4))));
+    s.assert_named("b", op!(= b, int!(5, 4)));
+    assert_eq!(s.check(), SolveResult::Unsat);
+    assert_eq!(s.unsat_core(), ["a", "b"]);
+
+    // assumptions are not part of the core, only the assertions they conflict with
+    let mut s = Solver::new();
+    let a = s.new_variable("x1", 4);
+    s.assert_named("a", op!(= a, int!(3, 4)));
+    assert_eq!(s.check(), SolveResult::Sat);
+    assert!(s.unsat_core().is_empty());
+    assert_eq!(
+        s.check_with_assumptions(&[op!(= a, int!(4, 4))]),
+        SolveResult::Unsat
+    );
+    assert_eq!(s.unsat_core(), ["a"]);
+}
+
+#[test]
+fn smtlib_named() {
+    let input = "(declare-fun x1 () (_ BitVec 4))
+(assert (! (= x1 #x3) :named a))
+(assert (= x1 #x4))
+(check-sat)
+(get-unsat-core)
+";
+    let problem = smtlib::parse(input).unwrap();
+    assert_eq!(ast::dump(&problem), input.trim_end());
+    match &problem[1] {
+        Statement::AssertNamed(name, expr) => {
+            assert_eq!(name, "a");
+            assert_eq!(*expr, op!(= Expression::Variable("x1".into()), int!(3, 4)));
+        }
+        statement => panic!("{}", statement),
+    }
+}
+
+#[test]
+fn smtlib_errors() {
+    // `(` and `!` are separate tokens
+    let input = "(declare-fun x1 () (_ BitVec 4))\n(assert ( ! (= x1 #x3) :named a))\n";
+    let problem = smtlib::parse(input).unwrap();
+    assert!(matches!(&problem[1], Statement::AssertNamed(name, _) if name == "a"));
+
+    let error = |input: &str| {
+        let err = smtlib::parse(input).unwrap_err();
+        (err.line, err.column, err.kind)
+    };
+    assert_eq!(
+        error("(assert (! x1 :name a))"),
+        (1, 15, SmtlibErrorKind::Expected(":named"))
+    );
+    assert_eq!(
+        error("(declare-fun x1 () Bool)\n(assert (! x1 :named"),
+        (2, 21, SmtlibErrorKind::Expected("<name>"))
+    );
+    assert_eq!(
+        error("(check-sat)\n(assert x1)"),
+        (1, 2, SmtlibErrorKind::MisplacedCommand("check-sat"))
+    );
+    assert_eq!(
+        error("(assert x1)\n(get-unsat-core)\n(check-sat)"),
+        (2, 2, SmtlibErrorKind::MisplacedCommand("get-unsat-core"))
+    );
+    assert_eq!(
+        error("(check-sat)\n(get-unsat-core)\n(check-sat)"),
+        (2, 2, SmtlibErrorKind::MisplacedCommand("get-unsat-core"))
+    );
+    assert_eq!(
+        error("(assert (bvfoo x1 x2))"),
+        (1, 10, SmtlibErrorKind::UnknownOperator("bvfoo".into()))
+    );
+    assert_eq!(
+        error("(get-model)"),
+        (1, 2, SmtlibErrorKind::UnknownCommand("get-model".into()))
+    );
+    assert_eq!(
+        error("(assert (= x1"),
+        (1, 14, SmtlibErrorKind::UnexpectedEof)
+    );
+    assert_eq!(
+        error("(assert (= x1 #y1))"),
+        (1, 15, SmtlibErrorKind::InvalidConstant("#y1".into()))
+    );
+    assert_eq!(
+        error("(assert (= x1 #xZZ))"),
+        (1, 15, SmtlibErrorKind::InvalidConstant("#xZZ".into()))
+    );
+    assert_eq!(
+        error("(assert (= x1 #b012 ))"),
+        (1, 15, SmtlibErrorKind::InvalidConstant("#b012".into()))
+    );
 }